@@ -0,0 +1,322 @@
+// This file is part of Sillyverse.
+// Copyright (C) 2017-2020, Aidin Gharibnavaz <aidin@aidinhut.com>
+//
+// Sillyverse is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Sillyverse is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
+
+/// This module provides an interface to the compiler, so other crates can
+/// assemble programs without writing them to files.
+
+pub mod translator;
+
+use std::collections::HashMap;
+
+/// Number of the words that are addressable by the machine. Memory size of
+/// the machine is a u16, so it's at most 65535 words.
+const MAX_PROGRAM_SIZE: usize = std::u16::MAX as usize;
+
+/// A line of the source code, and what it's translated to.
+pub struct ListingLine {
+    line_number: usize,
+    address: u16,
+    words: Vec<u16>,
+    source: String,
+}
+
+impl ListingLine {
+
+    /// Number of the line in the source code, starting from one.
+    pub fn get_line_number(&self) -> usize {
+        return self.line_number;
+    }
+
+    /// Address of the first word of the line.
+    pub fn get_address(&self) -> u16 {
+        return self.address;
+    }
+
+    /// Words that are emitted for this line. Empty for comments, labels, etc.
+    pub fn get_words(&self) -> &Vec<u16> {
+        return &self.words;
+    }
+
+    /// The line as it is in the source code.
+    pub fn get_source(&self) -> &String {
+        return &self.source;
+    }
+}
+
+/// Result of assembling a program.
+pub struct Assembly {
+    words: Vec<u16>,
+    machine_memory: Option<u16>,
+    listing: Vec<ListingLine>,
+}
+
+impl Assembly {
+
+    /// Binary words of the program.
+    pub fn get_words(&self) -> &Vec<u16> {
+        return &self.words;
+    }
+
+    /// Memory size that the program requested with the `.machine` directive, if any.
+    pub fn get_machine_memory(&self) -> Option<u16> {
+        return self.machine_memory;
+    }
+
+    /// Every line of the source code, and the words that it's translated to.
+    pub fn get_listing(&self) -> &Vec<ListingLine> {
+        return &self.listing;
+    }
+}
+
+/// Assembles the specified source code.
+/// Returns error if any of the lines failed to compile.
+pub fn assemble(source: &str) -> Result<Assembly, String> {
+
+    // First pass finds the addresses of the labels. Labels are translated to
+    // zero in this pass, so forward references don't fail.
+    let mut labels: HashMap<String, u16> = HashMap::new();
+    assemble_pass(&mut translator::Translator::new(), source, Some(&mut labels))?;
+
+    // A new translator, so named constants are defined again.
+    let mut translator = translator::Translator::new();
+    translator.set_labels(labels);
+
+    return assemble_pass(&mut translator, source, None);
+}
+
+/// Translates all lines of the source code.
+///
+/// @labels: If provided, addresses of the label definitions are recorded in it.
+fn assemble_pass(translator: &mut translator::Translator, source: &str,
+                 mut labels: Option<&mut HashMap<String, u16>>) -> Result<Assembly, String> {
+
+    let mut assembly = Assembly {
+        words: Vec::new(),
+        machine_memory: None,
+        listing: Vec::new(),
+    };
+
+    for (line_num, line) in source.lines().enumerate() {
+        let address = assembly.words.len();
+
+        if let Err(error) = assemble_line(translator, line, &mut assembly, &mut labels) {
+            // Line numbers start from one, as in the editors.
+            return Err(format!("Compile failed at line: {} [{}]\n{}", line_num + 1, line.trim(), error));
+        }
+
+        let words = assembly.words[address..].to_vec();
+        assembly.listing.push(ListingLine {
+            line_number: line_num + 1,
+            address: address as u16,
+            words: words,
+            source: String::from(line),
+        });
+    }
+
+    return Ok(assembly);
+}
+
+/// Translates a single line of the source code, and adds the result to the assembly.
+fn assemble_line(translator: &mut translator::Translator, line: &str, assembly: &mut Assembly,
+                 labels: &mut Option<&mut HashMap<String, u16>>) -> Result<(), String> {
+
+    let line_content = String::from(line);
+
+    if let Some(memory) = translator.parse_machine_directive(&line_content)? {
+        assembly.machine_memory = Some(memory);
+        return Ok(());
+    }
+
+    if translator.parse_equ_directive(&line_content)? {
+        return Ok(());
+    }
+
+    if let Some(address) = translator.parse_org_directive(&line_content)? {
+        if (address as usize) < assembly.words.len() {
+            return Err(format!("ORG address [{}] is behind the current position [{}]",
+                               address, assembly.words.len()));
+        }
+        // Padding with zeros up to the address.
+        assembly.words.resize(address as usize, 0);
+        return Ok(());
+    }
+
+    if let Some(name) = translator.parse_label(&line_content)? {
+        if let Some(ref mut labels) = *labels {
+            if labels.contains_key(&name) {
+                return Err(format!("Duplicated label: [{}]", name));
+            }
+            labels.insert(name, assembly.words.len() as u16);
+        }
+        return Ok(());
+    }
+
+    let words = translator.translate_line_words(line_content)?;
+
+    if assembly.words.len() + words.len() > MAX_PROGRAM_SIZE {
+        return Err(format!("Program doesn't fit in the addressable memory [{} words]", MAX_PROGRAM_SIZE));
+    }
+    assembly.words.extend(words);
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn assemble_source() {
+        let assembly = assemble(".machine memory=32\n\
+                                 ; A comment\n\
+                                 SET R1 120\n\
+                                 \n\
+                                 ADD R1 R2 ; Another comment\n\
+                                 LOAD_IMMEDIATE R3 40000\n").unwrap();
+
+        assert_eq!(assembly.get_words(), &vec![0b0110_001_001111000u16, 0b0010_000001_000010u16,
+                                               0b1011_011_000000000u16, 40000u16]);
+        assert_eq!(assembly.get_machine_memory(), Some(32));
+
+        let assembly = assemble("NOP").unwrap();
+        assert_eq!(assembly.get_words(), &vec![0u16]);
+        assert_eq!(assembly.get_machine_memory(), None);
+
+        let result = assemble("NOP\nBAD R1\n");
+        assert_eq!(result.is_err(), true);
+        assert_eq!(result.err().unwrap(), "Compile failed at line: 2 [BAD R1]\nUnknown operation: [bad]");
+    }
+
+    #[test]
+    fn assemble_labels() {
+        let assembly = assemble("SET R1 3\n\
+                                 loop:\n\
+                                 DEC_SKIP_IF_ZERO R1\n\
+                                 SET R7 @loop\n\
+                                 JUMP R7\n\
+                                 LOAD_IMMEDIATE R7 @end\n\
+                                 JUMP R7\n\
+                                 end:\n\
+                                 DATA @end\n").unwrap();
+
+        assert_eq!(assembly.get_words(), &vec![0b0110_001_000000011u16,
+                                               0b0000_000111_000001u16,
+                                               0b0110_111_000000001u16, // Backward reference
+                                               0b0000_000001_000111u16,
+                                               0b1011_111_000000000u16,
+                                               7u16, // Forward reference
+                                               0b0000_000001_000111u16,
+                                               7u16]);
+
+        let result = assemble("loop:\nNOP\nloop:\n");
+        assert_eq!(result.err().unwrap(), "Compile failed at line: 3 [loop:]\nDuplicated label: [loop]");
+
+        let result = assemble("NOP\nSET R1 @nowhere\n");
+        assert_eq!(result.err().unwrap(), "Compile failed at line: 2 [SET R1 @nowhere]\nUndefined label: [nowhere]");
+    }
+
+    #[test]
+    fn assemble_org() {
+        let assembly = assemble("NOP\n\
+                                 ORG 4\n\
+                                 label:\n\
+                                 DATA @label\n\
+                                 ORG 5\n\
+                                 HALT\n").unwrap();
+
+        assert_eq!(assembly.get_words(), &vec![0u16, 0u16, 0u16, 0u16, 4u16, 0b0000000000_000011u16]);
+
+        let result = assemble("NOP\nNOP\nORG 1\n");
+        assert_eq!(result.err().unwrap(),
+                   "Compile failed at line: 3 [ORG 1]\nORG address [1] is behind the current position [2]");
+    }
+
+    #[test]
+    fn assemble_equ() {
+        let assembly = assemble("EQU MAX 500\n\
+                                 SET R0 $MAX\n\
+                                 DATA $MAX\n").unwrap();
+        let expected = assemble("SET R0 500\nDATA 500\n").unwrap();
+
+        assert_eq!(assembly.get_words(), expected.get_words());
+
+        let result = assemble("SET R0 $MAX\nEQU MAX 500\n");
+        assert_eq!(result.err().unwrap(), "Compile failed at line: 1 [SET R0 $MAX]\nUndefined constant: [max]");
+    }
+
+    #[test]
+    fn assemble_listing() {
+        let assembly = assemble("; A comment\n\
+                                 SET R1 120\n\
+                                 LOAD_IMMEDIATE R2 1000\n").unwrap();
+
+        let listing = assembly.get_listing();
+        assert_eq!(listing.len(), 3);
+
+        assert_eq!(listing[0].get_line_number(), 1);
+        assert_eq!(listing[0].get_address(), 0);
+        assert_eq!(listing[0].get_words(), &Vec::<u16>::new());
+        assert_eq!(listing[0].get_source(), "; A comment");
+
+        assert_eq!(listing[1].get_line_number(), 2);
+        assert_eq!(listing[1].get_address(), 0);
+        assert_eq!(listing[1].get_words(), &vec![0b0110_001_001111000u16]);
+
+        assert_eq!(listing[2].get_line_number(), 3);
+        assert_eq!(listing[2].get_address(), 1);
+        assert_eq!(listing[2].get_words(), &vec![0b1011_010_000000000u16, 1000u16]);
+        assert_eq!(listing[2].get_source(), "LOAD_IMMEDIATE R2 1000");
+    }
+
+    #[test]
+    fn assemble_reserve() {
+        let assembly = assemble("EQU SIZE 3\n\
+                                 NOP\n\
+                                 RESERVE $SIZE\n\
+                                 end:\n\
+                                 DATA @end\n").unwrap();
+
+        assert_eq!(assembly.get_words(), &vec![0u16, 0u16, 0u16, 0u16, 4u16]);
+
+        let result = assemble("RESERVE 0\n");
+        assert_eq!(result.err().unwrap(), "Compile failed at line: 1 [RESERVE 0]\nCount of RESERVE can't be zero.");
+
+        let result = assemble("RESERVE 65535\n");
+        assert_eq!(result.is_ok(), true);
+
+        let result = assemble("NOP\nNOP\nRESERVE 65535\n");
+        assert_eq!(result.err().unwrap(),
+                   "Compile failed at line: 3 [RESERVE 65535]\nProgram doesn't fit in the addressable memory [65535 words]");
+    }
+
+    #[test]
+    fn assemble_max_program_size() {
+        // Exactly fills the largest memory. Label of the last word is still addressable.
+        let assembly = assemble("RESERVE 65534\n\
+                                 end:\n\
+                                 DATA @end\n").unwrap();
+        assert_eq!(assembly.get_words().len(), 65535);
+        assert_eq!(assembly.get_words()[65534], 65534);
+
+        // One word more.
+        let result = assemble("RESERVE 65534\n\
+                               NOP\n\
+                               NOP\n");
+        assert_eq!(result.err().unwrap(),
+                   "Compile failed at line: 3 [NOP]\nProgram doesn't fit in the addressable memory [65535 words]");
+    }
+}
@@ -14,72 +14,161 @@
 // You should have received a copy of the GNU General Public License
 // along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
 
+extern crate compiler;
+
 use std::env;
 use std::process;
-use std::io::BufReader;
-use std::io::BufRead;
-use std::io::BufWriter;
-use std::fs::File;
+use std::fs;
+use std::io;
 use std::io::Write;
 
-mod translator;
-
 
 fn print_usage(program_name: String) {
     println!(" ");
-    println!("Usage: {} input-file", program_name);
+    println!("Usage: {} [--listing] input-file [output-file]", program_name);
     println!(" ");
+    println!("  output-file  Defaults to <input-file>.bin");
+    println!("  --listing  Writes a listing of the assembly into <output>.lst");
+    println!(" ");
+}
+
+/// Returns path of the metadata file of the specified output.
+/// Metadata file contains `key=value` lines that describe the machine the
+/// program needs, e.g. `memory=256`. It's only created if the source has a
+/// `.machine` directive.
+fn metadata_path(output_path: &String) -> String {
+    return format!("{}.meta", output_path);
+}
+
+/// Returns path of the listing file of the specified output.
+fn listing_path(output_path: &String) -> String {
+    return format!("{}.lst", output_path);
+}
+
+/// Writes the listing of the assembly. Each line has the line number, address,
+/// the emitted word and the source. Next words of a multi-word line come on
+/// their own lines.
+fn write_listing(assembly: &compiler::Assembly, sink: &mut dyn Write) -> io::Result<()> {
+    for line in assembly.get_listing().iter() {
+        match line.get_words().first() {
+            Some(word) => writeln!(sink, "{:5}  0x{:04x}  0b{:016b}  {}",
+                                   line.get_line_number(), line.get_address(), word,
+                                   line.get_source())?,
+            None => writeln!(sink, "{:5}  {:6}  {:18}  {}",
+                             line.get_line_number(), "", "", line.get_source())?,
+        }
+
+        for (index, word) in line.get_words().iter().enumerate().skip(1) {
+            writeln!(sink, "{:5}  0x{:04x}  0b{:016b}",
+                     "", line.get_address() as usize + index, word)?;
+        }
+    }
+
+    return Ok(());
+}
+
+/// Writes the content into a temporary file next to the path, then renames it
+/// to the path. So the file at the path is either the old one or the complete
+/// new one.
+fn write_through_temp(path: &String, content: &[u8]) -> io::Result<()> {
+    let temp_path = format!("{}.tmp", path);
+
+    let result = fs::write(&temp_path, content).and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        // Ignoring the error, because the temp file may not even be created.
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    return result;
 }
 
-fn compile_file(input_path: &String, output_path: &String) {
-    let input_file = File::open(input_path).expect("Could not open input file.");
-    let output_file = File::create(output_path).expect("Could not open output file.");
+/// Compiles the input file and writes the binary into the output file.
+///
+/// Output is first written into a temporary file next to the output path, and
+/// is renamed to the output path only if the whole compile succeeded. So on
+/// any error, no partial output file will be left behind.
+///
+/// @listing_path: If provided, listing of the assembly is written into it,
+///     after the binary is written.
+fn compile_file(input_path: &String, output_path: &String, listing_path: Option<&String>)
+    -> Result<(), String> {
+    let source = match fs::read_to_string(input_path) {
+        Ok(s) => s,
+        Err(error) => return Err(format!("Could not read input file [{}]: {}", input_path, error)),
+    };
 
-    let input_file_reader = BufReader::new(&input_file);
-    let mut output_file_writer = BufWriter::new(&output_file);
+    let assembly = compiler::assemble(&source)?;
 
-    let translator = translator::Translator::new();
+    let mut output: Vec<u8> = Vec::with_capacity(assembly.get_words().len() * 2);
+    for instruction in assembly.get_words().iter() {
+        output.push(((instruction & 0b1111111100000000u16) >> 8) as u8);
+        output.push(*instruction as u8);
+    }
+
+    // Metadata is written before the binary, so a new binary is never paired
+    // with a stale metadata.
+    let metadata_file_path = metadata_path(output_path);
+    match assembly.get_machine_memory() {
+        Some(memory) => {
+            let metadata = format!("memory={}\n", memory);
+            if let Err(error) = write_through_temp(&metadata_file_path, metadata.as_bytes()) {
+                return Err(format!("Could not write metadata file [{}]: {}",
+                                   metadata_file_path, error));
+            }
+        },
+        None => {
+            // Removing metadata of the previous compiles, if any.
+            let _ = fs::remove_file(&metadata_file_path);
+        },
+    }
 
-    for (line_num, line) in input_file_reader.lines().enumerate() {
+    if let Err(error) = write_through_temp(output_path, &output) {
+        return Err(format!("Could not write output file [{}]: {}", output_path, error));
+    }
 
-        if line.is_err() {
-            eprintln!("Could not read line from the input file. Line: {}", line_num);
-            eprintln!("{}", line.unwrap_err());
-            process::exit(2);
+    if let Some(listing_file_path) = listing_path {
+        let mut listing: Vec<u8> = Vec::new();
+        if let Err(error) = write_listing(&assembly, &mut listing) {
+            return Err(format!("Could not write the listing: {}", error));
         }
 
-        let line_content = line.unwrap();
-
-        let instruction = match translator.translate_line(line_content) {
-            Ok(option) => match option {
-                None => continue,
-                Some(v) => v,
-            },
-            Err(error) => {
-                eprintln!("Compile failed at line: {}", line_num);
-                eprintln!("{}", error);
-                process::exit(3);
-            },
-        };
-
-        let instruction_bytes = [((instruction & 0b1111111100000000u16) >> 8) as u8,
-                                         instruction as u8];
-        output_file_writer.write_all(&instruction_bytes)
-            .expect("Could not write to output file.");
+        if let Err(error) = write_through_temp(listing_file_path, &listing) {
+            return Err(format!("Could not write listing file [{}]: {}", listing_file_path, error));
+        }
     }
+
+    return Ok(());
 }
 
 fn main() {
-    let mut args = env::args();
-    if args.len() != 2 {
-        print_usage(args.nth(0).unwrap());
+    let mut args: Vec<String> = env::args().collect();
+    let program_name = args.remove(0);
+
+    let with_listing = args.contains(&String::from("--listing"));
+    args.retain(|arg| arg != "--listing");
+
+    if args.len() != 1 && args.len() != 2 {
+        print_usage(program_name);
         process::exit(1);
     }
 
-    let input_file = args.nth(1).unwrap();
-    let output_file = format!("{}.bin", input_file);
+    let input_file = args[0].clone();
+    let output_file = match args.get(1) {
+        Some(path) => path.clone(),
+        None => format!("{}.bin", input_file),
+    };
 
-    compile_file(&input_file, &output_file);
+    let listing_file_path = listing_path(&output_file);
+    let result = if with_listing {
+        compile_file(&input_file, &output_file, Some(&listing_file_path))
+    } else {
+        compile_file(&input_file, &output_file, None)
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(2);
+    }
 }
 
 
@@ -88,7 +177,10 @@ mod tests {
 
     use super::*;
     use std::env::temp_dir;
+    use std::fs::File;
     use std::io::Read;
+    use std::io::Write;
+    use std::path::Path;
 
     #[test]
     fn application_1() {
@@ -112,7 +204,7 @@ mod tests {
         let input_path = String::from(assembly_file.to_str().unwrap());
         let output_path = format!("{}.bin", input_path);
 
-        compile_file(&input_path, &output_path);
+        compile_file(&input_path, &output_path, None).unwrap();
 
         let mut output_file = File::open(output_path).unwrap();
         let mut output_content: Vec<u8> = Vec::new();
@@ -132,4 +224,163 @@ mod tests {
             assert_eq!(output_content[i], expected_result[i]);
         }
     }
+
+    #[test]
+    fn invalid_output_path() {
+        let mut assembly_file = temp_dir();
+        assembly_file.push("test_invalid_output_path_9d8s7f1");
+
+        let mut f = File::create(&assembly_file).unwrap();
+        f.write_all(b"NOP\nNOP\n").unwrap();
+        f.flush().unwrap();
+
+        let mut output_file = temp_dir();
+        output_file.push("not_existed_dir_j3k8d7a");
+        output_file.push("output.bin");
+
+        let input_path = String::from(assembly_file.to_str().unwrap());
+        let output_path = String::from(output_file.to_str().unwrap());
+
+        let result = compile_file(&input_path, &output_path, None);
+        assert_eq!(result.is_err(), true);
+        assert!(result.unwrap_err().contains("Could not write output file"));
+
+        assert_eq!(output_file.exists(), false);
+    }
+
+    #[test]
+    fn no_partial_output() {
+        let mut assembly_file = temp_dir();
+        assembly_file.push("test_no_partial_output_m2n8c6q");
+
+        let mut f = File::create(&assembly_file).unwrap();
+        f.write_all(b"NOP\nNOP\nBAD_INSTRUCTION\nNOP\n").unwrap();
+        f.flush().unwrap();
+
+        let input_path = String::from(assembly_file.to_str().unwrap());
+        let output_path = format!("{}.bin", input_path);
+
+        let result = compile_file(&input_path, &output_path, None);
+        assert_eq!(result.is_err(), true);
+        assert_eq!(result.unwrap_err(),
+                   "Compile failed at line: 3 [BAD_INSTRUCTION]\nUnknown operation: [bad_instruction]");
+
+        // Neither output nor the temporary file should exist.
+        assert_eq!(Path::new(&output_path).exists(), false);
+        assert_eq!(Path::new(&format!("{}.tmp", output_path)).exists(), false);
+    }
+
+    #[test]
+    fn machine_metadata() {
+        let mut assembly_file = temp_dir();
+        assembly_file.push("test_machine_metadata_k3j7d9x");
+
+        let mut f = File::create(&assembly_file).unwrap();
+        f.write_all(b".machine memory=256\nNOP\nNOP\n").unwrap();
+        f.flush().unwrap();
+
+        let input_path = String::from(assembly_file.to_str().unwrap());
+        let output_path = format!("{}.bin", input_path);
+
+        compile_file(&input_path, &output_path, None).unwrap();
+
+        let metadata = fs::read_to_string(metadata_path(&output_path)).unwrap();
+        assert_eq!(metadata, "memory=256\n");
+
+        // Directive doesn't emit anything.
+        let output_content = fs::read(&output_path).unwrap();
+        assert_eq!(output_content, vec![0u8, 0u8, 0u8, 0u8]);
+
+        // Without the directive, there should be no metadata.
+        let mut f = File::create(&assembly_file).unwrap();
+        f.write_all(b"NOP\nNOP\n").unwrap();
+        f.flush().unwrap();
+
+        compile_file(&input_path, &output_path, None).unwrap();
+        assert_eq!(Path::new(&metadata_path(&output_path)).exists(), false);
+    }
+
+    #[test]
+    fn org_directive() {
+        let mut assembly_file = temp_dir();
+        assembly_file.push("test_org_directive_p28dm4q");
+
+        let mut f = File::create(&assembly_file).unwrap();
+        f.write_all(b"ORG 4\nNOP\nHALT\n").unwrap();
+        f.flush().unwrap();
+
+        let input_path = String::from(assembly_file.to_str().unwrap());
+        let output_path = format!("{}.bin", input_path);
+
+        compile_file(&input_path, &output_path, None).unwrap();
+
+        let output_content = fs::read(&output_path).unwrap();
+        // Four zero words, then NOP and HALT.
+        assert_eq!(output_content, vec![0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8,
+                                        0u8, 0u8,
+                                        0u8, 0b00000011u8]);
+    }
+
+    #[test]
+    fn listing() {
+        let mut assembly_file = temp_dir();
+        assembly_file.push("test_listing_w72hd0c");
+
+        let mut f = File::create(&assembly_file).unwrap();
+        f.write_all(b"; Listing\nNOP\nADD R1 R2\n").unwrap();
+        f.flush().unwrap();
+
+        let input_path = String::from(assembly_file.to_str().unwrap());
+        let output_path = format!("{}.bin", input_path);
+
+        let listing_file_path = listing_path(&output_path);
+        compile_file(&input_path, &output_path, Some(&listing_file_path)).unwrap();
+
+        let listing = fs::read_to_string(&listing_file_path).unwrap();
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].trim(), "1                              ; Listing");
+        assert_eq!(lines[2], "    3  0x0001  0b0010000001000010  ADD R1 R2");
+    }
+
+    #[test]
+    fn no_listing_on_failure() {
+        let mut assembly_file = temp_dir();
+        assembly_file.push("test_no_listing_on_failure_t61kc3v");
+
+        let mut f = File::create(&assembly_file).unwrap();
+        f.write_all(b"NOP\nBAD_INSTRUCTION\n").unwrap();
+        f.flush().unwrap();
+
+        let input_path = String::from(assembly_file.to_str().unwrap());
+        let output_path = format!("{}.bin", input_path);
+        let listing_file_path = listing_path(&output_path);
+
+        assert_eq!(compile_file(&input_path, &output_path, Some(&listing_file_path)).is_err(), true);
+
+        assert_eq!(Path::new(&listing_file_path).exists(), false);
+        assert_eq!(Path::new(&format!("{}.tmp", listing_file_path)).exists(), false);
+    }
+
+    #[test]
+    fn custom_output_path() {
+        let mut assembly_file = temp_dir();
+        assembly_file.push("test_custom_output_path_f82kd7s");
+
+        let mut f = File::create(&assembly_file).unwrap();
+        f.write_all(b"NOP\nHALT\n").unwrap();
+        f.flush().unwrap();
+
+        let mut output_file = temp_dir();
+        output_file.push("custom_output_z93jd8c.out");
+        let _ = fs::remove_file(&output_file);
+
+        let input_path = String::from(assembly_file.to_str().unwrap());
+        let output_path = String::from(output_file.to_str().unwrap());
+
+        compile_file(&input_path, &output_path, None).unwrap();
+
+        let output_content = fs::read(&output_file).unwrap();
+        assert_eq!(output_content, vec![0u8, 0u8, 0u8, 0b00000011u8]);
+    }
 }
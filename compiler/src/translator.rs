@@ -17,10 +17,18 @@
 /// Contains functions to translate assembly literals to their equivalent binary instructions.
 
 use std::collections::HashMap;
+use std::num::ParseIntError;
 
 
 pub struct Translator {
     operations_map: HashMap<&'static str, fn(Vec<String>) -> Result<u16, String>>,
+    // Operations that are translated to more than one word.
+    multi_word_operations_map: HashMap<&'static str, fn(Vec<String>) -> Result<Vec<u16>, String>>,
+    // Addresses of the labels. It's None when the addresses are not known yet
+    // (first pass of the assembler), and then every label is translated to zero.
+    labels: Option<HashMap<String, u16>>,
+    // Named constants that are defined by EQU.
+    constants: HashMap<String, u16>,
 }
 
 impl Translator {
@@ -33,6 +41,7 @@ impl Translator {
         map.insert("subroutine", subroutine);
         map.insert("return", return_subroutine);
         map.insert("syscall", syscall);
+        map.insert("halt", halt);
         map.insert("copy", copy);
         map.insert("jump", jump);
         map.insert("skip_if_zero", skip_if_zero);
@@ -41,29 +50,84 @@ impl Translator {
         map.insert("skip_if_equal", skip_if_equal);
         map.insert("skip_if_greater", skip_if_greater);
         map.insert("set", set);
-
+        map.insert("popcount", popcount);
+        map.insert("clz", count_leading_zeros);
+        map.insert("ctz", count_trailing_zeros);
+        map.insert("dec_skip_if_zero", dec_skip_if_zero);
+        map.insert("increment", increment);
+        map.insert("decrement", decrement);
+        map.insert("push", push);
+        map.insert("pop", pop);
+        map.insert("jump_link", jump_link);
+        map.insert("bfx", bit_field_extract);
+        map.insert("bfi", bit_field_insert);
+        map.insert("shift_left", shift_left);
+        map.insert("shift_right", shift_right);
+        map.insert("swap", swap);
+        map.insert("compare", compare);
+        map.insert("take", take);
+        map.insert("add_wrapping", add_wrapping);
+        map.insert("skip_if_greater_signed", skip_if_greater_signed);
+        map.insert("fill", fill);
+
+        let mut multi_word_map: HashMap<&'static str, fn(Vec<String>) -> Result<Vec<u16>, String>> =
+            HashMap::new();
+
+        multi_word_map.insert("load_immediate", load_immediate);
+        multi_word_map.insert("string", string);
+        multi_word_map.insert("reserve", reserve);
 
         Translator {
             operations_map: map,
+            multi_word_operations_map: multi_word_map,
+            labels: None,
+            constants: HashMap::new(),
         }
     }
 
+    /// Sets addresses of the labels. Before calling this, labels are translated
+    /// to zero, so the size of the program can be calculated.
+    pub fn set_labels(&mut self, labels: HashMap<String, u16>) {
+        self.labels = Some(labels);
+    }
+
     /// Translates one single line into its binary representation.
     /// Returns None if this line presents nothing (a comment or empty line).
+    /// Returns error for operations that are translated to more than one word.
+    /// Use `translate_line_words` for them.
     pub fn translate_line(&self, line: String) -> Result<Option<u16>, String> {
+        let words = self.translate_line_words(line)?;
+
+        return match words.len() {
+            0 => Ok(None),
+            1 => Ok(Some(words[0])),
+            _ => Err(format!("Line is translated to {} words, expected one.", words.len())),
+        };
+    }
+
+    /// Translates one single line into its binary representation, which may be
+    /// more than one word.
+    /// Returns an empty list if this line presents nothing (a comment or empty line).
+    pub fn translate_line_words(&self, line: String) -> Result<Vec<u16>, String> {
 
         let line = line.trim();
 
         if line.starts_with(";") {
             // A comment line.
-            return Ok(None);
+            return Ok(Vec::new());
         }
 
         let line_parts = self.extract_parts(line);
 
         if line_parts.len() == 0 {
             // Nothing was in this line.
-            return Ok(None);
+            return Ok(Vec::new());
+        }
+
+        let line_parts = self.resolve_symbols(line_parts)?;
+
+        if let Some(func) = self.multi_word_operations_map.get(&*line_parts[0]) {
+            return func(line_parts);
         }
 
         // Finding function to handle this operation.
@@ -75,26 +139,237 @@ impl Translator {
         // Executing the func.
         let result = func(line_parts)?;
 
-        return Ok(Some(result));
+        return Ok(vec![result]);
+    }
+
+    /// Parses an ORG directive, e.g. `ORG 0x20`, which moves the current position
+    /// of the program to the specified address.
+    /// Returns None if the line is not an ORG directive, otherwise returns the address.
+    pub fn parse_org_directive(&self, line: &String) -> Result<Option<u16>, String> {
+
+        let line_parts = self.extract_parts(line.trim());
+
+        if line_parts.len() == 0 || line_parts[0] != "org" {
+            return Ok(None);
+        }
+
+        if line_parts.len() != 2 {
+            return Err(format!("ORG requires exactly one argument, {} given.",
+                               line_parts.len() - 1));
+        }
+
+        return match parse_constant(&line_parts[1]) {
+            Ok(address) => Ok(Some(address)),
+            Err(e) => Err(format!(
+                "Address of ORG must be a positive number less than 65536: [{}] Error: {}",
+                line_parts[1], e)),
+        };
+    }
+
+    /// Parses a label definition, e.g. `loop:`, which should be on its own line.
+    /// Returns None if the line is not a label definition, otherwise returns
+    /// name of the label.
+    pub fn parse_label(&self, line: &String) -> Result<Option<String>, String> {
+
+        let line_parts = self.extract_parts(line.trim());
+
+        if line_parts.len() == 0 || !line_parts[0].ends_with(":") {
+            return Ok(None);
+        }
+
+        if line_parts.len() != 1 {
+            return Err(format!("Label should be on its own line: [{}]", line_parts[0]));
+        }
+
+        let name = line_parts[0].trim_end_matches(':');
+
+        if !is_valid_name(name) {
+            return Err(format!(
+                "Label should be letters, digits and underscore, and can't start with a digit: [{}]",
+                name));
+        }
+
+        return Ok(Some(String::from(name)));
+    }
+
+    /// Parses an EQU directive, e.g. `EQU MAX 500`, and defines the named constant.
+    /// Constant can be used afterward as `$MAX`.
+    /// Returns false if the line is not an EQU directive.
+    pub fn parse_equ_directive(&mut self, line: &String) -> Result<bool, String> {
+
+        let line_parts = self.extract_parts(line.trim());
+
+        if line_parts.len() == 0 || line_parts[0] != "equ" {
+            return Ok(false);
+        }
+
+        if line_parts.len() != 3 {
+            return Err(format!("EQU requires exactly two arguments, {} given.",
+                               line_parts.len() - 1));
+        }
+
+        if !is_valid_name(&line_parts[1]) {
+            return Err(format!(
+                "Name of EQU should be letters, digits and underscore, and can't start with a digit: [{}]",
+                line_parts[1]));
+        }
+
+        if self.constants.contains_key(&line_parts[1]) {
+            return Err(format!("Constant is already defined: [{}]", line_parts[1]));
+        }
+
+        let value = match parse_constant(&line_parts[2]) {
+            Ok(v) => v,
+            Err(e) => return Err(format!(
+                "Value of EQU must be a positive number less than 65536: [{}] Error: {}",
+                line_parts[2], e)),
+        };
+
+        self.constants.insert(line_parts[1].clone(), value);
+
+        return Ok(true);
+    }
+
+    /// Replaces label references, e.g. `@loop`, with address of the label, and
+    /// named constants, e.g. `$MAX`, with their value.
+    fn resolve_symbols(&self, line_parts: Vec<String>) -> Result<Vec<String>, String> {
+        let mut result: Vec<String> = Vec::with_capacity(line_parts.len());
+
+        for (index, part) in line_parts.iter().enumerate() {
+            if index == 0 || !(part.starts_with("@") || part.starts_with("$")) {
+                result.push(part.clone());
+                continue;
+            }
+
+            if !SYMBOL_OPERATIONS.contains(&&*line_parts[0]) {
+                return Err(format!(
+                    "{} does not accept a label or a named constant: [{}]. Operands are addresses; \
+                     load the value into a register with SET or LOAD_IMMEDIATE, then use the register.",
+                    line_parts[0].to_uppercase(), part));
+            }
+
+            let value = if part.starts_with("$") {
+                match self.constants.get(&part[1..]) {
+                    Some(value) => *value,
+                    None => return Err(format!("Undefined constant: [{}]", &part[1..])),
+                }
+            } else {
+                match self.labels {
+                    None => 0,
+                    Some(ref labels) => match labels.get(&part[1..]) {
+                        Some(address) => *address,
+                        None => return Err(format!("Undefined label: [{}]", &part[1..])),
+                    },
+                }
+            };
+
+            result.push(value.to_string());
+        }
+
+        return Ok(result);
+    }
+
+    /// Parses a `.machine` directive, e.g. `.machine memory=256`, which describes
+    /// the machine that the program needs.
+    /// Returns None if the line is not a `.machine` directive, otherwise returns
+    /// the requested memory size.
+    pub fn parse_machine_directive(&self, line: &String) -> Result<Option<u16>, String> {
+
+        let line_parts = self.extract_parts(line.trim());
+
+        if line_parts.len() == 0 || line_parts[0] != ".machine" {
+            return Ok(None);
+        }
+
+        if line_parts.len() != 2 {
+            return Err(format!(".MACHINE requires exactly one argument, {} given.",
+                               line_parts.len() - 1));
+        }
+
+        let setting: Vec<&str> = line_parts[1].splitn(2, '=').collect();
+        if setting.len() != 2 || setting[0] != "memory" {
+            return Err(format!("Unknown .MACHINE setting: [{}]. Expected memory=<size>",
+                               line_parts[1]));
+        }
+
+        let memory = match setting[1].parse::<u16>() {
+            Ok(v) => v,
+            Err(e) => return Err(format!(
+                "Memory of .MACHINE must be a positive number less than 65536: [{}] Error: {}",
+                setting[1], e)),
+        };
+
+        if memory == 0 {
+            return Err(String::from("Memory of .MACHINE cannot be zero."));
+        }
+
+        return Ok(Some(memory));
     }
 
     /// Splits the line from spaces, and returns a list of line parts. i.e. operation
-    /// and its parameters. It converts all words to lower case.
+    /// and its parameters. It converts all words to lower case, except character
+    /// literals (e.g. 'A') and string literals (e.g. "Hi") which are kept as they
+    /// are, spaces included. Escape sequences of the strings are kept as they are too.
     fn extract_parts(&self, line: &str) -> Vec<String> {
-        let line_split = line.split(" ");
         let mut result: Vec<String> = Vec::new();
+        let mut part = String::new();
+        let mut in_character_literal = false;
+        let mut in_string_literal = false;
+        let mut escaped = false;
+
+        for c in line.chars() {
+            if in_character_literal {
+                part.push(c);
+                if c == '\'' {
+                    in_character_literal = false;
+                }
+                continue;
+            }
 
-        for part in line_split {
-            if part.starts_with(" ") || part == "" {
-                // Two spaces together, or an empty line.
+            if in_string_literal {
+                part.push(c);
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    in_string_literal = false;
+                }
                 continue;
             }
-            if part.starts_with(";") {
-                // There's comment from now on.
-                break;
+
+            if c.is_whitespace() || c == ',' {
+                // End of a part. Any run of spaces, tabs and commas is a single separator,
+                // so operands can be written as `COPY R1, M6` too.
+                if part.len() > 0 {
+                    result.push(part);
+                    part = String::new();
+                }
+                continue;
+            }
+
+            if part.len() == 0 {
+                if c == ';' {
+                    // There's comment from now on.
+                    break;
+                }
+                if c == '\'' {
+                    in_character_literal = true;
+                    part.push(c);
+                    continue;
+                }
+                if c == '"' {
+                    in_string_literal = true;
+                    part.push(c);
+                    continue;
+                }
             }
 
-            result.push(part.to_lowercase());
+            part.extend(c.to_lowercase());
+        }
+
+        if part.len() > 0 {
+            result.push(part);
         }
 
         return result;
@@ -141,7 +416,7 @@ fn translate_address(address_str: &String) -> Result<u8, String> {
     };
 
     if address_value > 7 {
-        return Err(format!("Expected address less than 7, found: {}", address_value));
+        return Err(format!("Expected address at most 7, found: {}", address_value));
     }
 
     // Appending address type and its value.
@@ -149,15 +424,108 @@ fn translate_address(address_str: &String) -> Result<u8, String> {
 }
 
 
+/// Operations that accept a label (`@name`) or a named constant (`$NAME`) as
+/// their constant.
+const SYMBOL_OPERATIONS: [&str; 4] = ["data", "set", "load_immediate", "reserve"];
+
+/// Whether the name is valid for a label or a named constant.
+fn is_valid_name(name: &str) -> bool {
+    return name.len() > 0 && !name.chars().next().unwrap().is_digit(10) &&
+        name.chars().all(|c| c.is_alphanumeric() || c == '_');
+}
+
+/// Operations that accept the RP (Register plus PC) address type. Other operations
+/// will fail at runtime if they receive such an address.
+const REGISTER_PLUS_PC_OPERATIONS: [&str; 3] = ["jump", "subroutine", "jump_link"];
+
+/// Translates the specified operand of an operation to its 6 bit address.
+/// Returns error if the operation does not support type of the address.
+///
+/// @args: Operation and its operands, as passed to translator functions.
+/// @position: Index of the operand in the "args" (first operand is one).
+fn translate_operand(args: &Vec<String>, position: usize) -> Result<u8, String> {
+    let address = translate_address(&args[position])?;
+
+    if address & 0b00110000u8 == 0b00100000u8 &&
+        !REGISTER_PLUS_PC_OPERATIONS.contains(&&*args[0]) {
+        return Err(format!("{} does not accept RP address type in operand {}: [{}]",
+                           args[0].to_uppercase(), position, args[position]));
+    }
+
+    return Ok(address);
+}
+
+/// Returns error if the operation doesn't have exactly the expected number of operands.
+/// The error names the operation and lists the operands that are found, so a typo
+/// in the line is easy to spot.
+fn check_arguments_count(args: &Vec<String>, expected: usize) -> Result<(), String> {
+    let found = args.len() - 1;
+
+    if found == expected {
+        return Ok(());
+    }
+
+    let expected_text = match expected {
+        0 => "doesn't accept any operands",
+        1 => "requires exactly one operand",
+        2 => "requires exactly two operands",
+        _ => "requires exactly three operands",
+    };
+
+    return Err(format!("{} {}, {} given: [{}]",
+                       args[0].to_uppercase(), expected_text, found, args[1..].join(" ")));
+}
+
+/// Parses a numeric constant. Decimal is the default, "0x" prefix
+/// means hexadecimal, and "0b" prefix means binary.
+fn parse_constant(value: &str) -> Result<u16, ParseIntError> {
+    if value.starts_with("0x") {
+        return u16::from_str_radix(&value[2..], 16);
+    }
+
+    if value.starts_with("0b") {
+        return u16::from_str_radix(&value[2..], 2);
+    }
+
+    return value.parse::<u16>();
+}
+
+/// Translates a single quoted ASCII character, e.g. 'A', to its code.
+fn character_literal(literal: &str) -> Result<u16, String> {
+    let characters: Vec<char> = literal.chars().collect();
+
+    if characters.len() != 3 || characters[2] != '\'' {
+        return Err(format!("Character literal should be exactly one character: [{}]", literal));
+    }
+
+    if !characters[1].is_ascii() {
+        return Err(format!("Character literal should be an ASCII character: [{}]", literal));
+    }
+
+    return Ok(characters[1] as u16);
+}
+
 /// DATA means no operation, just a data that will be stored on that block
 /// of memory.
 fn data(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 2 {
-        return Err(format!("DATA requires exactly one argument, {} found.", args.len() - 1));
+    check_arguments_count(&args, 1)?;
+
+    if args[1].starts_with("'") {
+        return character_literal(&args[1]);
+    }
+
+    if args[1].starts_with("-") {
+        // Negative numbers are stored as two's complement.
+        return match args[1].parse::<i16>() {
+            Ok(v) => Ok(v as u16),
+            Err(error) => Err(format!(
+                "Negative argument of DATA must be a number not less than -32768. Argument: [{}] Error: {}",
+                args[1], error)),
+        };
     }
 
-    let data = match args[1].parse::<u16>() {
+    let data = match parse_constant(&args[1]) {
         Ok(v) => v,
         Err(error) => return Err(format!(
             "Argument of DATA must be a positive number less than 65536. Argument: [{}] Error: {}",
@@ -169,49 +537,46 @@ fn data(args: Vec<String>) -> Result<u16, String> {
 
 fn nop(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 1 {
-        return Err(String::from("NOP doesn't accept arguments."));
-    }
+    check_arguments_count(&args, 0)?;
 
     return Ok(0u16);
 }
 
 fn subroutine(args: Vec<String>) -> Result<u16, String> {
-    if args.len() != 2 {
-        return Err(format!("SUBROUTINE requires exactly one arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 1)?;
 
-    let address = translate_address(&args[1])?;
+    let address = translate_operand(&args, 1)?;
 
     return Ok(0b0000_000011_000000u16 | (address as u16));
 }
 
 fn return_subroutine(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 1 {
-        return Err(String::from("RETURN doesn't accept arguments."));
-    }
+    check_arguments_count(&args, 0)?;
 
     return Ok(0b0000000000_000010u16);
 }
 
 fn syscall(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 1 {
-        return Err(String::from("SYSCALL doesn't accept arguments."));
-    }
+    check_arguments_count(&args, 0)?;
 
     return Ok(0b0000000000_000001u16);
 }
 
+fn halt(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 0)?;
+
+    return Ok(0b0000000000_000011u16);
+}
+
 fn copy(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 3 {
-        return Err(format!("COPY requires exactly two arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 2)?;
 
-    let first_address = translate_address(&args[1])?;
-    let second_address = translate_address(&args[2])?;
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
 
     let first_address: u16 = (first_address as u16) <<6;
     return Ok(0b0001_000000000000u16 | first_address | (second_address as u16));
@@ -219,34 +584,109 @@ fn copy(args: Vec<String>) -> Result<u16, String> {
 
 fn jump(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 2 {
-        return Err(format!("JUMP requires exactly one arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 1)?;
 
-    let address = translate_address(&args[1])?;
+    let address = translate_operand(&args, 1)?;
 
     return Ok(0b0000_000001_000000u16 | (address as u16));
 }
 
 fn skip_if_zero(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 2 {
-        return Err(format!("SKIP_IF_ZERO requires exactly one arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 1)?;
 
-    let address = translate_address(&args[1])?;
+    let address = translate_operand(&args, 1)?;
 
     return Ok(0b0000_000010_000000u16 | (address as u16));
 }
 
+fn popcount(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_000100_000000u16 | (address as u16));
+}
+
+fn count_leading_zeros(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_000101_000000u16 | (address as u16));
+}
+
+fn count_trailing_zeros(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_000110_000000u16 | (address as u16));
+}
+
+fn dec_skip_if_zero(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_000111_000000u16 | (address as u16));
+}
+
+fn increment(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_001000_000000u16 | (address as u16));
+}
+
+fn decrement(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_001001_000000u16 | (address as u16));
+}
+
+fn push(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_001010_000000u16 | (address as u16));
+}
+
+fn pop(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_001011_000000u16 | (address as u16));
+}
+
+fn jump_link(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 1)?;
+
+    let address = translate_operand(&args, 1)?;
+
+    return Ok(0b0000_001100_000000u16 | (address as u16));
+}
+
 fn add(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 3 {
-        return Err(format!("ADD requires exactly two arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 2)?;
 
-    let first_address = translate_address(&args[1])?;
-    let second_address = translate_address(&args[2])?;
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
 
     let first_address: u16 = (first_address as u16) <<6;
     return Ok(0b0010_000000000000u16 | first_address | (second_address as u16));
@@ -254,12 +694,10 @@ fn add(args: Vec<String>) -> Result<u16, String> {
 
 fn subtract(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 3 {
-        return Err(format!("SUBTRACT requires exactly two arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 2)?;
 
-    let first_address = translate_address(&args[1])?;
-    let second_address = translate_address(&args[2])?;
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
 
     let first_address: u16 = (first_address as u16) <<6;
     return Ok(0b0011_000000000000u16 | first_address | (second_address as u16));
@@ -267,37 +705,29 @@ fn subtract(args: Vec<String>) -> Result<u16, String> {
 
 fn skip_if_equal(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 3 {
-        return Err(
-            format!("SKIP_IF_EQUAL requires exactly two arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 2)?;
 
-    let first_address = translate_address(&args[1])?;
-    let second_address = translate_address(&args[2])?;
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
 
     let first_address: u16 = (first_address as u16) <<6;
-    return Ok(0b00101_000000000000u16 | first_address | (second_address as u16));
+    return Ok(0b0100_000000000000u16 | first_address | (second_address as u16));
 }
 
 fn skip_if_greater(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 3 {
-        return Err(
-            format!("SKIP_IF_GREATER requires exactly two arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 2)?;
 
-    let first_address = translate_address(&args[1])?;
-    let second_address = translate_address(&args[2])?;
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
 
     let first_address: u16 = (first_address as u16) <<6;
-    return Ok(0b00110_000000000000u16 | first_address | (second_address as u16));
+    return Ok(0b0101_000000000000u16 | first_address | (second_address as u16));
 }
 
 fn set(args: Vec<String>) -> Result<u16, String> {
 
-    if args.len() != 3 {
-        return Err(format!("SET requires exactly two arguments, {} given.", args.len() -1));
-    }
+    check_arguments_count(&args, 2)?;
 
     if !args[1].starts_with("r") {
         return Err(format!("SET only accepts register addresses. Found: {}", args[1]));
@@ -310,10 +740,10 @@ fn set(args: Vec<String>) -> Result<u16, String> {
     };
 
     if register_number > 7 {
-        return Err(format!("Register number should be less than 7: {}", args[1]));
+        return Err(format!("Register number should be at most 7: {}", args[1]));
     }
 
-    let constant = match args[2].parse::<u16>() {
+    let constant = match parse_constant(&args[2]) {
         Ok(v) => v,
         Err(e) => return Err(format!(
             "Second argument of SET must be a positive number: [{}] Error: {}", args[2], e)),
@@ -326,221 +756,991 @@ fn set(args: Vec<String>) -> Result<u16, String> {
     return Ok(0b0110_000_000000000u16 | ((register_number as u16) <<9) | constant);
 }
 
-#[cfg(test)]
-mod tests {
-
-    use super::*;
-
-    #[test]
-    fn bad_line() {
-        let translator = Translator::new();
+/// STRING stores a text in the memory, one character per word, followed by
+/// a zero word. i.e. `STRING "Hi"`. `\"`, `\\` and `\n` escapes are supported.
+fn string(args: Vec<String>) -> Result<Vec<u16>, String> {
 
-        let result = translator.translate_line(String::from("BAD_COMMAND  12"));
-        assert_eq!(result.is_err(), true);
+    check_arguments_count(&args, 1)?;
 
-        let result = translator.translate_line(String::from("  3000 DATA "));
-        assert_eq!(result.is_err(), true);
+    let literal = &args[1];
+    if literal.len() < 2 || !literal.starts_with("\"") || !literal.ends_with("\"") {
+        return Err(format!("STRING requires a quoted text: [{}]", literal));
     }
 
-    #[test]
-    fn comment() {
-        let translator = Translator::new();
+    let mut result: Vec<u16> = Vec::new();
+    let mut escaped = false;
+
+    for c in literal[1..literal.len() - 1].chars() {
+        let character = if escaped {
+            escaped = false;
+            match c {
+                'n' => '\n',
+                '"' => '"',
+                '\\' => '\\',
+                _ => return Err(format!("Unknown escape sequence [\\{}] in: [{}]", c, literal)),
+            }
+        } else if c == '\\' {
+            escaped = true;
+            continue;
+        } else {
+            c
+        };
 
-        let result = translator.translate_line(String::from("; comment")).unwrap();
-        assert_eq!(result.is_none(), true);
+        if !character.is_ascii() {
+            return Err(format!("STRING should only contain ASCII characters: [{}]", literal));
+        }
 
-        let result = translator.translate_line(String::from("    ; comment")).unwrap();
-        assert_eq!(result.is_none(), true);
+        result.push(character as u16);
     }
 
-    #[test]
-    fn data() {
-        let translator = Translator::new();
+    if escaped {
+        // The last backslash escaped the closing quote.
+        return Err(format!("STRING is not terminated: [{}]", literal));
+    }
 
-        let result = translator.translate_line(String::from("DATA     3000")).unwrap();
-        assert_eq!(result.unwrap(), 3000);
+    // Zero terminator.
+    result.push(0);
 
-        let result = translator.translate_line(String::from(" Data  65535  ")).unwrap();
-        assert_eq!(result.unwrap(), 65535);
+    return Ok(result);
+}
 
-        // Errors
-        let result = translator.translate_line(String::from(" DATA  "));
-        assert_eq!(result.is_err(), true);
+/// RESERVE fills the specified number of words with zero. i.e. `RESERVE 16`.
+fn reserve(args: Vec<String>) -> Result<Vec<u16>, String> {
 
-        let result = translator.translate_line(String::from(" DATA  120  200"));
-        assert_eq!(result.is_err(), true);
+    check_arguments_count(&args, 1)?;
 
-        let result = translator.translate_line(String::from(" DATA  0xFF "));
-        assert_eq!(result.is_err(), true);
-    }
+    let count = match parse_constant(&args[1]) {
+        Ok(v) => v,
+        Err(e) => return Err(format!(
+            "Count of RESERVE must be a positive number less than 65536: [{}] Error: {}", args[1], e)),
+    };
 
-    #[test]
-    fn nop() {
-        let translator = Translator::new();
+    if count == 0 {
+        return Err(String::from("Count of RESERVE can't be zero."));
+    }
 
-        let result = translator.translate_line(String::from("NOP")).unwrap();
-        assert_eq!(result.unwrap(), 0u16);
+    return Ok(vec![0u16; count as usize]);
+}
 
-        let result = translator.translate_line(String::from("nop  ")).unwrap();
-        assert_eq!(result.unwrap(), 0u16);
+/// Loads a full 16 bits constant into a register. The constant is stored
+/// in the word after the instruction.
+fn load_immediate(args: Vec<String>) -> Result<Vec<u16>, String> {
 
-        let result = translator.translate_line(String::from("nOp ; Comment")).unwrap();
-        assert_eq!(result.unwrap(), 0u16);
+    check_arguments_count(&args, 2)?;
 
-        let result = translator.translate_line(String::from("NOP  R1"));
-        assert_eq!(result.is_err(), true);
+    if !args[1].starts_with("r") {
+        return Err(format!("LOAD_IMMEDIATE only accepts register addresses. Found: {}", args[1]));
     }
 
-    #[test]
-    fn subroutine() {
-        let translator = Translator::new();
+    let register_number = match args[1].replace("r", "").parse::<u8>() {
+        Ok(v) => v,
+        Err(e) =>
+            return Err(format!("Provided register is not a number: [{}]. Error: {}", args[1], e)),
+    };
 
-        let result = translator.translate_line(String::from("SubRoutine R2 ")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000011_000010u16);
+    if register_number > 7 {
+        return Err(format!("Register number should be at most 7: {}", args[1]));
+    }
 
-        let result = translator.translate_line(String::from("SUBROUTIne  m5 ;comment ")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000011_010101u16);
+    let constant = match parse_constant(&args[2]) {
+        Ok(v) => v,
+        Err(e) => return Err(format!(
+            "Constant of LOAD_IMMEDIATE must be a positive number less than 65536: [{}] Error: {}",
+            args[2], e)),
+    };
 
-        let result = translator.translate_line(String::from("subroutine Rp5")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000011_100101u16);
+    return Ok(vec![0b1011_000_000000000u16 | ((register_number as u16) << 9), constant]);
+}
 
-        let result = translator.translate_line(String::from("SUBroutine RPm5")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000011_110101u16);
+/// Translates a bit field operation, e.g. `BFX R1 4 3`, which has a register, the
+/// lowest bit of the field, and width of the field.
+///
+/// @operation_code: Code of the operation, without its operands.
+fn bit_field(args: Vec<String>, operation_code: u16) -> Result<u16, String> {
 
-        // Testing errors.
+    let name = args[0].to_uppercase();
 
-        let result = translator.translate_line(String::from("SUBROUTINE "));
-        assert_eq!(result.is_err(), true);
+    check_arguments_count(&args, 3)?;
 
-        let result = translator.translate_line(String::from("Subroutine ; comment"));
-        assert_eq!(result.is_err(), true);
+    if !args[1].starts_with("r") {
+        return Err(format!("{} only accepts register addresses. Found: {}", name, args[1]));
+    }
+
+    let register_number = match args[1].replace("r", "").parse::<u8>() {
+        Ok(v) => v,
+        Err(e) =>
+            return Err(format!("Provided register is not a number: [{}]. Error: {}", args[1], e)),
+    };
+
+    if register_number > 7 {
+        return Err(format!("Register number should be at most 7: {}", args[1]));
+    }
+
+    let lowest_bit = match args[2].parse::<u16>() {
+        Ok(v) => v,
+        Err(e) => return Err(format!(
+            "Lowest bit of {} must be a positive number: [{}] Error: {}", name, args[2], e)),
+    };
+
+    let width = match args[3].parse::<u16>() {
+        Ok(v) => v,
+        Err(e) => return Err(format!(
+            "Width of {} must be a positive number: [{}] Error: {}", name, args[3], e)),
+    };
+
+    if width == 0 || lowest_bit as u32 + width as u32 > 16 {
+        return Err(format!(
+            "Bit field of {} should fit in 16 bits, and can't be empty. Lowest bit: [{}] Width: [{}]",
+            name, lowest_bit, width));
+    }
+
+    return Ok(operation_code | ((register_number as u16) << 9) | (lowest_bit << 5) | width);
+}
+
+/// Extracts a bit field of a register into the same register.
+fn bit_field_extract(args: Vec<String>) -> Result<u16, String> {
+    return bit_field(args, 0b0111_000_0000_00000u16);
+}
+
+/// Inserts lowest bits of R0 into a bit field of a register.
+fn bit_field_insert(args: Vec<String>) -> Result<u16, String> {
+    return bit_field(args, 0b1000_000_0000_00000u16);
+}
+
+fn shift_left(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 2)?;
+
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
+
+    let first_address: u16 = (first_address as u16) <<6;
+    return Ok(0b1001_000000000000u16 | first_address | (second_address as u16));
+}
+
+fn shift_right(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 2)?;
+
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
+
+    let first_address: u16 = (first_address as u16) <<6;
+    return Ok(0b1010_000000000000u16 | first_address | (second_address as u16));
+}
+
+fn swap(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 2)?;
+
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
+
+    let first_address: u16 = (first_address as u16) <<6;
+    return Ok(0b1100_000000000000u16 | first_address | (second_address as u16));
+}
+
+fn compare(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 2)?;
+
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
+
+    let first_address: u16 = (first_address as u16) <<6;
+    return Ok(0b1101_000000000000u16 | first_address | (second_address as u16));
+}
+
+fn take(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 2)?;
+
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
+
+    let first_address: u16 = (first_address as u16) <<6;
+    return Ok(0b1110_000000000000u16 | first_address | (second_address as u16));
+}
+
+/// Extended operations share the last operation code. They are: 4 bits operation,
+/// 2 bits extended operation, and two 5 bits operands.
+///
+/// @operation_code: Operation code, including the extended operation bits.
+fn extended_double_operand(args: Vec<String>, operation_code: u16) -> Result<u16, String> {
+
+    check_arguments_count(&args, 2)?;
+
+    let first_address = translate_operand(&args, 1)?;
+    let second_address = translate_operand(&args, 2)?;
+
+    // Register number of an address is at most 7, so it fits in three bits.
+    let first_operand: u16 = address_to_extended_operand(first_address) << 5;
+    return Ok(operation_code | first_operand | address_to_extended_operand(second_address));
+}
+
+/// Converts a 6 bits address (tt0rrr) to a 5 bits operand (ttrrr).
+fn address_to_extended_operand(address: u8) -> u16 {
+    return (((address & 0b110000u8) >> 1) | (address & 0b000111u8)) as u16;
+}
+
+fn add_wrapping(args: Vec<String>) -> Result<u16, String> {
+    return extended_double_operand(args, 0b1111_00_0000000000u16);
+}
+
+fn skip_if_greater_signed(args: Vec<String>) -> Result<u16, String> {
+    return extended_double_operand(args, 0b1111_01_0000000000u16);
+}
+
+/// Writes a value into a range of the memory. Operands are three registers:
+/// start address, length, and the value. Format: 1111 10 0 sss lll vvv
+fn fill(args: Vec<String>) -> Result<u16, String> {
+
+    check_arguments_count(&args, 3)?;
+
+    let mut instruction = 0b1111_10_0_000_000_000u16;
+
+    for position in 1..4 {
+        let address = translate_address(&args[position])?;
+        if address & 0b110000u8 != 0 {
+            return Err(format!("FILL only accepts register addresses. Found: {}", args[position]));
+        }
+
+        instruction |= (address as u16) << (3 * (3 - position));
+    }
+
+    return Ok(instruction);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn bad_line() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("BAD_COMMAND  12"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("  3000 DATA "));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn comment() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("; comment")).unwrap();
+        assert_eq!(result.is_none(), true);
+
+        let result = translator.translate_line(String::from("    ; comment")).unwrap();
+        assert_eq!(result.is_none(), true);
+    }
+
+    #[test]
+    fn whitespaces() {
+        let translator = Translator::new();
+
+        let expected = translator.translate_line(String::from("COPY R1 M6")).unwrap();
+
+        let result = translator.translate_line(String::from("COPY\tR1\tM6")).unwrap();
+        assert_eq!(result, expected);
+
+        let result = translator.translate_line(String::from("\tCOPY \t R1\t\t M6  \t")).unwrap();
+        assert_eq!(result, expected);
+
+        let result = translator.translate_line(String::from("\t; comment")).unwrap();
+        assert_eq!(result.is_none(), true);
+
+        let result = translator.translate_line(String::from("COPY\tR1\tM6\t;comment")).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn commas() {
+        let translator = Translator::new();
+
+        let expected = translator.translate_line(String::from("COPY R1 M6")).unwrap();
+        let result = translator.translate_line(String::from("COPY R1, M6")).unwrap();
+        assert_eq!(result, expected);
+        let result = translator.translate_line(String::from("COPY R1,M6")).unwrap();
+        assert_eq!(result, expected);
+        let result = translator.translate_line(String::from("COPY R1 , M6")).unwrap();
+        assert_eq!(result, expected);
+
+        let expected = translator.translate_line(String::from("ADD R0 R1")).unwrap();
+        let result = translator.translate_line(String::from("ADD R0, R1")).unwrap();
+        assert_eq!(result, expected);
+        let result = translator.translate_line(String::from("ADD\tR0,\tR1 ; comment, with comma")).unwrap();
+        assert_eq!(result, expected);
+
+        // Comma in a character literal is still a character.
+        let result = translator.translate_line(String::from("DATA ','")).unwrap();
+        assert_eq!(result.unwrap(), 44u16);
+    }
+
+    #[test]
+    fn data() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("DATA     3000")).unwrap();
+        assert_eq!(result.unwrap(), 3000);
+
+        let result = translator.translate_line(String::from(" Data  65535  ")).unwrap();
+        assert_eq!(result.unwrap(), 65535);
+
+        // Errors
+        let result = translator.translate_line(String::from(" DATA  "));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from(" DATA  120  200"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from(" DATA  0x10000 "));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from(" DATA  0x "));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from(" DATA  0xFG "));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn data_hexadecimal() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("DATA 0xFF")).unwrap();
+        assert_eq!(result.unwrap(), 255);
+
+        let result = translator.translate_line(String::from("DATA 0xFFFF")).unwrap();
+        assert_eq!(result.unwrap(), 65535);
+
+        let result = translator.translate_line(String::from("data 0x0")).unwrap();
+        assert_eq!(result.unwrap(), 0);
+    }
+
+    #[test]
+    fn data_binary() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("DATA 0b0000111100001111")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000111100001111u16);
+
+        let result = translator.translate_line(String::from("DATA 0b1111111111111111")).unwrap();
+        assert_eq!(result.unwrap(), 65535);
+
+        // Errors
+        let result = translator.translate_line(String::from("DATA 0b11111111111111111"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("DATA 0b102"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn data_negative() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("DATA -1")).unwrap();
+        assert_eq!(result.unwrap(), 65535);
+
+        let result = translator.translate_line(String::from("DATA -32768")).unwrap();
+        assert_eq!(result.unwrap(), 32768);
+
+        // Errors
+        let result = translator.translate_line(String::from("DATA -40000"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("DATA -0x1"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn data_character() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("DATA 'A'")).unwrap();
+        assert_eq!(result.unwrap(), 65);
+
+        let result = translator.translate_line(String::from("data 'a' ; lower case")).unwrap();
+        assert_eq!(result.unwrap(), 97);
+
+        let result = translator.translate_line(String::from("DATA ' '")).unwrap();
+        assert_eq!(result.unwrap(), 32);
+
+        let result = translator.translate_line(String::from("DATA ';'")).unwrap();
+        assert_eq!(result.unwrap(), 59);
+
+        // Errors
+        let result = translator.translate_line(String::from("DATA 'AB'"));
+        assert_eq!(result.unwrap_err(), "Character literal should be exactly one character: ['AB']");
+
+        let result = translator.translate_line(String::from("DATA ''"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("DATA 'A"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("DATA 'é'"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn machine_directive() {
+        let translator = Translator::new();
+
+        let result = translator.parse_machine_directive(&String::from(".machine memory=256"));
+        assert_eq!(result.unwrap(), Some(256));
+
+        let result = translator.parse_machine_directive(&String::from(" .MACHINE Memory=12 ;c"));
+        assert_eq!(result.unwrap(), Some(12));
+
+        // Not a directive.
+        let result = translator.parse_machine_directive(&String::from("SET R1 12"));
+        assert_eq!(result.unwrap(), None);
+
+        let result = translator.parse_machine_directive(&String::from("; .machine memory=12"));
+        assert_eq!(result.unwrap(), None);
+
+        // Errors
+        let result = translator.parse_machine_directive(&String::from(".machine"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.parse_machine_directive(&String::from(".machine memory=0"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.parse_machine_directive(&String::from(".machine memory=70000"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.parse_machine_directive(&String::from(".machine cpu=2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn nop() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("NOP")).unwrap();
+        assert_eq!(result.unwrap(), 0u16);
+
+        let result = translator.translate_line(String::from("nop  ")).unwrap();
+        assert_eq!(result.unwrap(), 0u16);
+
+        let result = translator.translate_line(String::from("nOp ; Comment")).unwrap();
+        assert_eq!(result.unwrap(), 0u16);
+
+        let result = translator.translate_line(String::from("NOP  R1"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn subroutine() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("SubRoutine R2 ")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000011_000010u16);
+
+        let result = translator.translate_line(String::from("SUBROUTIne  m5 ;comment ")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000011_010101u16);
+
+        let result = translator.translate_line(String::from("subroutine Rp5")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000011_100101u16);
+
+        let result = translator.translate_line(String::from("SUBroutine RPm5")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000011_110101u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("SUBROUTINE "));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("Subroutine ; comment"));
+        assert_eq!(result.is_err(), true);
 
         let result = translator.translate_line(String::from("SUBROUTINE R1 R4"));
         assert_eq!(result.is_err(), true);
 
-        let result = translator.translate_line(String::from("SUBROUTINE 14"));
+        let result = translator.translate_line(String::from("SUBROUTINE 14"));
+        assert_eq!(result.is_err(), true);
+
+    }
+
+    #[test]
+    fn return_subroutine() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("RETURN")).unwrap();
+        assert_eq!(result.unwrap(), 2u16);
+
+        let result = translator.translate_line(String::from("ReTuRn  ")).unwrap();
+        assert_eq!(result.unwrap(), 2u16);
+
+        let result = translator.translate_line(String::from("return ; A ;Comment")).unwrap();
+        assert_eq!(result.unwrap(), 2u16);
+
+        let result = translator.translate_line(String::from("RETURN  R1"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn syscall() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("  SYSCALL  ")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000000000_000001u16);
+
+        let result = translator.translate_line(String::from("SysCall ; A comment NOP ")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000000000_000001u16);
+
+        let result = translator.translate_line(String::from("SYSCALL  R1"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn halt() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("HALT")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000000000_000011u16);
+
+        let result = translator.translate_line(String::from("  halt ; Done")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000000000_000011u16);
+
+        let result = translator.translate_line(String::from("HALT R1"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn copy() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("COPY R1 M6")).unwrap();
+        assert_eq!(result.unwrap(), 0b0001_000001_010110u16);
+
+        let result = translator.translate_line(String::from("COPY   M2  RPM3")).unwrap();
+        assert_eq!(result.unwrap(), 0b0001_010010_110011u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("COPY  M2"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("COPY M20 M2"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("COPY ;bad copy"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("COPY 120 14"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("COPY RP1 R2"));
+        assert_eq!(result.unwrap_err(), "COPY does not accept RP address type in operand 1: [rp1]");
+
+        let result = translator.translate_line(String::from("COPY R1 RP2"));
+        assert_eq!(result.unwrap_err(), "COPY does not accept RP address type in operand 2: [rp2]");
+    }
+
+    #[test]
+    fn jump() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("JUMP R1  ")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000001_000001u16);
+
+        let result = translator.translate_line(String::from("jump  m3 ;comment R2 ")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000001_010011u16);
+
+        let result = translator.translate_line(String::from("JuMp Rp4")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000001_100100u16);
+
+        let result = translator.translate_line(String::from("JUmP RPm5")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000001_110101u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("JUMP "));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("JUMP ; comment"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("JUMP R1 R4"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("JUMP 14"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn skip_if_zero() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("SKIP_IF_ZERO R1  ")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000010_000001u16);
+
+        let result = translator.translate_line(String::from("skip_if_zero  m7 ;comment R2 ")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000010_010111u16);
+
+        let result = translator.translate_line(String::from("skip_IF_zero M0")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000010_010000u16);
+
+        let result = translator.translate_line(String::from("SKIP_IF_ZERO RPm5")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000010_110101u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("SKIP_IF_ZERO "));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SKIP_IF_ZERO ; comment"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SKIP_IF_ZERO M1 RP4"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SKIP_IF_ZERO R12"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SKIP_IF_ZERO 0"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SKIP_IF_ZERO RP0"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn popcount() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("POPCOUNT R1")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000100_000001u16);
+
+        let result = translator.translate_line(String::from("popcount  m4 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000100_010100u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("POPCOUNT "));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("POPCOUNT R1 R2"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("POPCOUNT R8"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn count_leading_zeros() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("CLZ R1")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000101_000001u16);
+
+        let result = translator.translate_line(String::from("clz  rpm3 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000101_110011u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("CLZ"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("CLZ R1 R2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn count_trailing_zeros() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("CTZ R7")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000110_000111u16);
+
+        let result = translator.translate_line(String::from("  ctz m0")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000110_010000u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("CTZ"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("CTZ M1 14"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn dec_skip_if_zero() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("DEC_SKIP_IF_ZERO R1")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000111_000001u16);
+
+        let result = translator.translate_line(String::from("dec_skip_if_zero  m5 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_000111_010101u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("DEC_SKIP_IF_ZERO"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("DEC_SKIP_IF_ZERO R1 R2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn increment() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("INCREMENT R1")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_001000_000001u16);
+
+        let result = translator.translate_line(String::from("increment  m6 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_001000_010110u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("INCREMENT"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("INCREMENT RP1"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn decrement() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("DECREMENT R7")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_001001_000111u16);
+
+        let result = translator.translate_line(String::from("decrement rpm2")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_001001_110010u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("DECREMENT R1 R2"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("DECREMENT RP1"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn load_immediate() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE R3 40000")).unwrap();
+        assert_eq!(result, vec![0b1011_011_000000000u16, 40000u16]);
+
+        let result = translator.translate_line_words(String::from("load_immediate  r0 65535 ; max")).unwrap();
+        assert_eq!(result, vec![0b1011_000_000000000u16, 65535u16]);
+
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE R1 0xFFFF")).unwrap();
+        assert_eq!(result, vec![0b1011_001_000000000u16, 0xFFFFu16]);
+
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE R2 0b1000000000000001")).unwrap();
+        assert_eq!(result, vec![0b1011_010_000000000u16, 0b1000000000000001u16]);
+
+        // Single word operations and empty lines.
+        let result = translator.translate_line_words(String::from("NOP")).unwrap();
+        assert_eq!(result, vec![0u16]);
+        let result = translator.translate_line_words(String::from("; comment")).unwrap();
+        assert_eq!(result, Vec::<u16>::new());
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("LOAD_IMMEDIATE R3 40000"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE R3 65536"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE R3 0x10000"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE M3 1"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE R8 1"));
         assert_eq!(result.is_err(), true);
 
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE R1"));
+        assert_eq!(result.is_err(), true);
     }
 
     #[test]
-    fn return_subroutine() {
+    fn string() {
         let translator = Translator::new();
 
-        let result = translator.translate_line(String::from("RETURN")).unwrap();
-        assert_eq!(result.unwrap(), 2u16);
+        let result = translator.translate_line_words(String::from("STRING \"Hi\"")).unwrap();
+        assert_eq!(result, vec![72u16, 105u16, 0u16]);
 
-        let result = translator.translate_line(String::from("ReTuRn  ")).unwrap();
-        assert_eq!(result.unwrap(), 2u16);
+        let result = translator.translate_line_words(String::from("string \"A b, C;\" ; comment")).unwrap();
+        assert_eq!(result, vec![65u16, 32u16, 98u16, 44u16, 32u16, 67u16, 59u16, 0u16]);
 
-        let result = translator.translate_line(String::from("return ; A ;Comment")).unwrap();
-        assert_eq!(result.unwrap(), 2u16);
+        let result = translator.translate_line_words(String::from("STRING \"\\\"q\\\"\\n\\\\\"")).unwrap();
+        assert_eq!(result, vec![34u16, 113u16, 34u16, 10u16, 92u16, 0u16]);
 
-        let result = translator.translate_line(String::from("RETURN  R1"));
+        let result = translator.translate_line_words(String::from("STRING \"\"")).unwrap();
+        assert_eq!(result, vec![0u16]);
+
+        // Testing errors.
+
+        let result = translator.translate_line_words(String::from("STRING"));
         assert_eq!(result.is_err(), true);
-    }
 
-    #[test]
-    fn syscall() {
-        let translator = Translator::new();
+        let result = translator.translate_line_words(String::from("STRING Hi"));
+        assert_eq!(result.is_err(), true);
 
-        let result = translator.translate_line(String::from("  SYSCALL  ")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000000000_000001u16);
+        let result = translator.translate_line_words(String::from("STRING \"Hi"));
+        assert_eq!(result.is_err(), true);
 
-        let result = translator.translate_line(String::from("SysCall ; A comment NOP ")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000000000_000001u16);
+        let result = translator.translate_line_words(String::from("STRING \"Hi\\\""));
+        assert_eq!(result.is_err(), true);
 
-        let result = translator.translate_line(String::from("SYSCALL  R1"));
+        let result = translator.translate_line_words(String::from("STRING \"\\t\""));
+        assert_eq!(result.unwrap_err(), "Unknown escape sequence [\\t] in: [\"\\t\"]");
+
+        let result = translator.translate_line_words(String::from("STRING \"Hi\" \"There\""));
         assert_eq!(result.is_err(), true);
     }
 
     #[test]
-    fn copy() {
+    fn reserve() {
         let translator = Translator::new();
 
-        let result = translator.translate_line(String::from("COPY R1 M6")).unwrap();
-        assert_eq!(result.unwrap(), 0b0001_000001_010110u16);
+        let result = translator.translate_line_words(String::from("RESERVE 3")).unwrap();
+        assert_eq!(result, vec![0u16, 0u16, 0u16]);
 
-        let result = translator.translate_line(String::from("COPY   RP2  RPM3")).unwrap();
-        assert_eq!(result.unwrap(), 0b0001_100010_110011u16);
+        let result = translator.translate_line_words(String::from("RESERVE 0x10")).unwrap();
+        assert_eq!(result, vec![0u16; 16]);
 
         // Testing errors.
 
-        let result = translator.translate_line(String::from("COPY  M2"));
-        assert_eq!(result.is_err(), true);
+        let result = translator.translate_line_words(String::from("RESERVE 0"));
+        assert_eq!(result.unwrap_err(), "Count of RESERVE can't be zero.");
 
-        let result = translator.translate_line(String::from("COPY M20 M2"));
+        let result = translator.translate_line_words(String::from("RESERVE"));
         assert_eq!(result.is_err(), true);
 
-        let result = translator.translate_line(String::from("COPY ;bad copy"));
+        let result = translator.translate_line_words(String::from("RESERVE -1"));
         assert_eq!(result.is_err(), true);
 
-        let result = translator.translate_line(String::from("COPY 120 14"));
+        let result = translator.translate_line_words(String::from("RESERVE 65536"));
         assert_eq!(result.is_err(), true);
     }
 
     #[test]
-    fn jump() {
+    fn org_directive() {
         let translator = Translator::new();
 
-        let result = translator.translate_line(String::from("JUMP R1  ")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000001_000001u16);
+        let result = translator.parse_org_directive(&String::from("ORG 4"));
+        assert_eq!(result.unwrap(), Some(4));
 
-        let result = translator.translate_line(String::from("jump  m3 ;comment R2 ")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000001_010011u16);
+        let result = translator.parse_org_directive(&String::from("  org 0x100 ; data")).unwrap();
+        assert_eq!(result, Some(256));
 
-        let result = translator.translate_line(String::from("JuMp Rp4")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000001_100100u16);
+        let result = translator.parse_org_directive(&String::from("NOP")).unwrap();
+        assert_eq!(result, None);
 
-        let result = translator.translate_line(String::from("JUmP RPm5")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000001_110101u16);
+        // Errors
+        assert_eq!(translator.parse_org_directive(&String::from("ORG")).is_err(), true);
+        assert_eq!(translator.parse_org_directive(&String::from("ORG 1 2")).is_err(), true);
+        assert_eq!(translator.parse_org_directive(&String::from("ORG R1")).is_err(), true);
+        assert_eq!(translator.parse_org_directive(&String::from("ORG 65536")).is_err(), true);
+    }
 
-        // Testing errors.
+    #[test]
+    fn equ_directive() {
+        let mut translator = Translator::new();
 
-        let result = translator.translate_line(String::from("JUMP "));
-        assert_eq!(result.is_err(), true);
+        assert_eq!(translator.parse_equ_directive(&String::from("EQU MAX 500")), Ok(true));
+        assert_eq!(translator.parse_equ_directive(&String::from("equ mask 0xFF00 ; comment")),
+                   Ok(true));
+        assert_eq!(translator.parse_equ_directive(&String::from("NOP")), Ok(false));
 
-        let result = translator.translate_line(String::from("JUMP ; comment"));
-        assert_eq!(result.is_err(), true);
+        let result = translator.translate_line(String::from("SET R0 $MAX")).unwrap();
+        let expected = translator.translate_line(String::from("SET R0 500")).unwrap();
+        assert_eq!(result, expected);
 
-        let result = translator.translate_line(String::from("JUMP R1 R4"));
+        let result = translator.translate_line(String::from("DATA $mask")).unwrap();
+        assert_eq!(result.unwrap(), 0xFF00);
+
+        // Errors
+        assert_eq!(translator.parse_equ_directive(&String::from("EQU MAX 10")).is_err(), true);
+        assert_eq!(translator.parse_equ_directive(&String::from("EQU 2MAX 10")).is_err(), true);
+        assert_eq!(translator.parse_equ_directive(&String::from("EQU MIN")).is_err(), true);
+        assert_eq!(translator.parse_equ_directive(&String::from("EQU MIN -1")).is_err(), true);
+
+        let result = translator.translate_line(String::from("DATA $MIN"));
+        assert_eq!(result.unwrap_err(), "Undefined constant: [min]");
+
+        // Range of SET is checked after substitution.
+        let result = translator.translate_line(String::from("SET R0 $mask"));
         assert_eq!(result.is_err(), true);
 
-        let result = translator.translate_line(String::from("JUMP 14"));
+        let result = translator.translate_line(String::from("COPY $MAX R1"));
         assert_eq!(result.is_err(), true);
     }
 
     #[test]
-    fn skip_if_zero() {
+    fn parse_label() {
         let translator = Translator::new();
 
-        let result = translator.translate_line(String::from("SKIP_IF_ZERO R1  ")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000010_000001u16);
+        let result = translator.parse_label(&String::from("loop:")).unwrap();
+        assert_eq!(result, Some(String::from("loop")));
 
-        let result = translator.translate_line(String::from("skip_if_zero  m7 ;comment R2 ")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000010_010111u16);
+        let result = translator.parse_label(&String::from("  Second_Loop2:  ; comment")).unwrap();
+        assert_eq!(result, Some(String::from("second_loop2")));
 
-        let result = translator.translate_line(String::from("skip_IF_zero Rp0")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000010_100000u16);
+        let result = translator.parse_label(&String::from("NOP")).unwrap();
+        assert_eq!(result, None);
 
-        let result = translator.translate_line(String::from("SKIP_IF_ZERO RPm5")).unwrap();
-        assert_eq!(result.unwrap(), 0b0000_000010_110101u16);
+        let result = translator.parse_label(&String::from("; loop:")).unwrap();
+        assert_eq!(result, None);
 
         // Testing errors.
 
-        let result = translator.translate_line(String::from("SKIP_IF_ZERO "));
-        assert_eq!(result.is_err(), true);
+        assert_eq!(translator.parse_label(&String::from("loop: NOP")).is_err(), true);
+        assert_eq!(translator.parse_label(&String::from(":")).is_err(), true);
+        assert_eq!(translator.parse_label(&String::from("2loop:")).is_err(), true);
+        assert_eq!(translator.parse_label(&String::from("lo-op:")).is_err(), true);
+    }
 
-        let result = translator.translate_line(String::from("SKIP_IF_ZERO ; comment"));
-        assert_eq!(result.is_err(), true);
+    #[test]
+    fn label_references() {
+        let mut translator = Translator::new();
 
-        let result = translator.translate_line(String::from("SKIP_IF_ZERO M1 RP4"));
-        assert_eq!(result.is_err(), true);
+        // Addresses of labels are not known yet.
+        let result = translator.translate_line(String::from("SET R7 @loop")).unwrap();
+        assert_eq!(result.unwrap(), 0b0110_111_000000000u16);
 
-        let result = translator.translate_line(String::from("SKIP_IF_ZERO R12"));
+        let mut labels = HashMap::new();
+        labels.insert(String::from("loop"), 12);
+        labels.insert(String::from("end"), 40000);
+        translator.set_labels(labels);
+
+        let result = translator.translate_line(String::from("SET R7 @Loop")).unwrap();
+        assert_eq!(result.unwrap(), 0b0110_111_000001100u16);
+
+        let result = translator.translate_line(String::from("DATA @end")).unwrap();
+        assert_eq!(result.unwrap(), 40000u16);
+
+        let result = translator.translate_line_words(String::from("LOAD_IMMEDIATE R1 @end")).unwrap();
+        assert_eq!(result, vec![0b1011_001_000000000u16, 40000u16]);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("SET R7 @end"));
         assert_eq!(result.is_err(), true);
 
-        let result = translator.translate_line(String::from("SKIP_IF_ZERO 0"));
+        let result = translator.translate_line(String::from("DATA @nowhere"));
+        assert_eq!(result.unwrap_err(), "Undefined label: [nowhere]");
+
+        let result = translator.translate_line(String::from("JUMP @loop"));
         assert_eq!(result.is_err(), true);
     }
 
@@ -551,8 +1751,8 @@ mod tests {
         let result = translator.translate_line(String::from("ADD R1 M6")).unwrap();
         assert_eq!(result.unwrap(), 0b0010_000001_010110u16);
 
-        let result = translator.translate_line(String::from("ADD   RP2  RPM3")).unwrap();
-        assert_eq!(result.unwrap(), 0b0010_100010_110011u16);
+        let result = translator.translate_line(String::from("ADD   R2  RPM3")).unwrap();
+        assert_eq!(result.unwrap(), 0b0010_000010_110011u16);
 
         // Testing errors.
 
@@ -567,6 +1767,9 @@ mod tests {
 
         let result = translator.translate_line(String::from("ADD"));
         assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("ADD R1 RP2"));
+        assert_eq!(result.unwrap_err(), "ADD does not accept RP address type in operand 2: [rp2]");
     }
 
     #[test]
@@ -576,8 +1779,8 @@ mod tests {
         let result = translator.translate_line(String::from("SUBTRACT R3 M5")).unwrap();
         assert_eq!(result.unwrap(), 0b0011_000011_010101u16);
 
-        let result = translator.translate_line(String::from("subtract   RP7  rpm3")).unwrap();
-        assert_eq!(result.unwrap(), 0b0011_100111_110011u16);
+        let result = translator.translate_line(String::from("subtract   RPM7  rpm3")).unwrap();
+        assert_eq!(result.unwrap(), 0b0011_110111_110011u16);
 
         // Testing errors.
 
@@ -592,6 +1795,9 @@ mod tests {
 
         let result = translator.translate_line(String::from("SUBTRACT"));
         assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SUBTRACT RP1 M2"));
+        assert_eq!(result.is_err(), true);
     }
 
     #[test]
@@ -599,10 +1805,10 @@ mod tests {
         let translator = Translator::new();
 
         let result = translator.translate_line(String::from("SKIP_IF_EQUAL R3 M6")).unwrap();
-        assert_eq!(result.unwrap(), 0b0101_000011_010110u16);
+        assert_eq!(result.unwrap(), 0b0100_000011_010110u16);
 
         let result = translator.translate_line(String::from("skip_if_equal   m2  RPM3")).unwrap();
-        assert_eq!(result.unwrap(), 0b0101_010010_110011u16);
+        assert_eq!(result.unwrap(), 0b0100_010010_110011u16);
 
         // Testing errors.
 
@@ -624,10 +1830,10 @@ mod tests {
         let translator = Translator::new();
 
         let result = translator.translate_line(String::from("   SKIP_IF_GREATER  R3 M6 ;M80")).unwrap();
-        assert_eq!(result.unwrap(), 0b0110_000011_010110u16);
+        assert_eq!(result.unwrap(), 0b0101_000011_010110u16);
 
         let result = translator.translate_line(String::from("skip_if_greater   M0  RPM3")).unwrap();
-        assert_eq!(result.unwrap(), 0b0110_010000_110011u16);
+        assert_eq!(result.unwrap(), 0b0101_010000_110011u16);
 
         // Testing errors.
 
@@ -657,6 +1863,9 @@ mod tests {
         let result = translator.translate_line(String::from("  SET   R0 0 ")).unwrap();
         assert_eq!(result.unwrap(), 0b0110_000_000000000u16);
 
+        let result = translator.translate_line(String::from("SET R1 0x1FF")).unwrap();
+        assert_eq!(result.unwrap(), 0b0110_001_111111111u16);
+
         // Checking errors.
 
         let result = translator.translate_line(String::from("  SET   R0 "));
@@ -671,6 +1880,9 @@ mod tests {
         let result = translator.translate_line(String::from("SET R0 512 "));
         assert_eq!(result.is_err(), true);
 
+        let result = translator.translate_line(String::from("SET R0 0x200"));
+        assert_eq!(result.is_err(), true);
+
         let result = translator.translate_line(String::from("SET R1 R12"));
         assert_eq!(result.is_err(), true);
 
@@ -682,4 +1894,277 @@ mod tests {
 
     }
 
+    #[test]
+    fn bit_field_extract() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("BFX R1 6 4")).unwrap();
+        assert_eq!(result.unwrap(), 0b0111_001_0110_00100u16);
+
+        let result = translator.translate_line(String::from(" bfx r7 0 16 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b0111_111_0000_10000u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("BFX R1 6"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("BFX M1 6 4"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("BFX R8 6 4"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("BFX R1 12 5"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("BFX R1 2 0"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn bit_field_insert() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("BFI R2 4 3")).unwrap();
+        assert_eq!(result.unwrap(), 0b1000_010_0100_00011u16);
+
+        let result = translator.translate_line(String::from("bfi r0 15 1")).unwrap();
+        assert_eq!(result.unwrap(), 0b1000_000_1111_00001u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("BFI R2 4 3 1"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("BFI R2 16 0"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("BFI R2 65535 3"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("BFI R2 a 3"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn shift_left() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("SHIFT_LEFT R1 R2")).unwrap();
+        assert_eq!(result.unwrap(), 0b1001_000001_000010u16);
+
+        let result = translator.translate_line(String::from("shift_left  m3 rpm4 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b1001_010011_110100u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("SHIFT_LEFT R1"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SHIFT_LEFT R1 RP2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn shift_right() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("SHIFT_RIGHT R7 M0")).unwrap();
+        assert_eq!(result.unwrap(), 0b1010_000111_010000u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("SHIFT_RIGHT"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SHIFT_RIGHT R1 R8"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn swap() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("SWAP R1 R2")).unwrap();
+        assert_eq!(result.unwrap(), 0b1100_000001_000010u16);
+
+        let result = translator.translate_line(String::from("swap  rpm3 m4 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b1100_110011_010100u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("SWAP R1"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SWAP RP1 R2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn compare() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("COMPARE R1 R2")).unwrap();
+        assert_eq!(result.unwrap(), 0b1101_000001_000010u16);
+
+        let result = translator.translate_line(String::from("compare  m3 rpm4 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b1101_010011_110100u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("COMPARE R1 R2 R3"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("COMPARE R1 RP2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn push_pop() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("PUSH R1")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_001010_000001u16);
+
+        let result = translator.translate_line(String::from("pop  rpm6 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_001011_110110u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("PUSH"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("POP RP1"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn register_range() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("COPY R7 R0")).unwrap();
+        assert_eq!(result.unwrap(), 0b0001_000111_000000u16);
+
+        let result = translator.translate_line(String::from("COPY R8 R0"));
+        assert_eq!(result.err().unwrap(), "Expected address at most 7, found: 8");
+
+        let result = translator.translate_line(String::from("SET R7 1")).unwrap();
+        assert_eq!(result.unwrap(), 0b0110_111_000000001u16);
+
+        let result = translator.translate_line(String::from("SET R8 1"));
+        assert_eq!(result.err().unwrap(), "Register number should be at most 7: r8");
+    }
+
+    #[test]
+    fn arguments_count() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("COPY R1 M6 M7"));
+        assert_eq!(result.err().unwrap(), "COPY requires exactly two operands, 3 given: [r1 m6 m7]");
+
+        let result = translator.translate_line(String::from("NOP extra ; comment"));
+        assert_eq!(result.err().unwrap(), "NOP doesn't accept any operands, 1 given: [extra]");
+
+        let result = translator.translate_line(String::from("JUMP"));
+        assert_eq!(result.err().unwrap(), "JUMP requires exactly one operand, 0 given: []");
+
+        let result = translator.translate_line(String::from("NOP ; fine"));
+        assert_eq!(result.is_ok(), true);
+    }
+
+    #[test]
+    fn take() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("TAKE R1 M5")).unwrap();
+        assert_eq!(result.unwrap(), 0b1110_000001_010101u16);
+
+        let result = translator.translate_line(String::from("take  rpm3 r4 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b1110_110011_000100u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("TAKE R1"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("TAKE R1 RP2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn add_wrapping() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("ADD_WRAPPING R1 R2")).unwrap();
+        assert_eq!(result.unwrap(), 0b1111_00_00001_00010u16);
+
+        let result = translator.translate_line(String::from("add_wrapping  rpm3 m7 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b1111_00_11011_01111u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("ADD_WRAPPING R1"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("ADD_WRAPPING R1 RP2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn skip_if_greater_signed() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("SKIP_IF_GREATER_SIGNED R1 R2")).unwrap();
+        assert_eq!(result.unwrap(), 0b1111_01_00001_00010u16);
+
+        let result = translator.translate_line(String::from("skip_if_greater_signed m6 rpm0")).unwrap();
+        assert_eq!(result.unwrap(), 0b1111_01_01110_11000u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("SKIP_IF_GREATER_SIGNED R1 R2 R3"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("SKIP_IF_GREATER_SIGNED RP1 R2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn jump_link() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("JUMP_LINK R1")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_001100_000001u16);
+
+        let result = translator.translate_line(String::from("jump_link  rp3 ;comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b0000_001100_100011u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("JUMP_LINK R1 R2"));
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn fill() {
+        let translator = Translator::new();
+
+        let result = translator.translate_line(String::from("FILL R1 R2 R3")).unwrap();
+        assert_eq!(result.unwrap(), 0b1111_10_0_001_010_011u16);
+
+        let result = translator.translate_line(String::from("fill r7 r0 r7 ; comment")).unwrap();
+        assert_eq!(result.unwrap(), 0b1111_10_0_111_000_111u16);
+
+        // Testing errors.
+
+        let result = translator.translate_line(String::from("FILL R1 R2"));
+        assert_eq!(result.is_err(), true);
+
+        let result = translator.translate_line(String::from("FILL R1 M2 R3"));
+        assert_eq!(result.err().unwrap(), "FILL only accepts register addresses. Found: m2");
+
+        let result = translator.translate_line(String::from("FILL R1 R2 R8"));
+        assert_eq!(result.is_err(), true);
+    }
 }
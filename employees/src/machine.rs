@@ -16,34 +16,105 @@
 
 use std::rc::Rc;
 use std::sync::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(test)]
+use compiler;
 use emulator::Emulator;
 use emulator::CPUState;
+use emulator::ClockError;
 use emulator::SysCallback;
+use shared_memory::SharedMemory;
 use syscalls;
+use tasks::queue::TasksQueue;
 
 
+/// Part of the machine that the sys calls work on.
+/// It's kept apart from the Machine, because sys calls are called in the middle
+/// of the Machine's clock, when the Machine itself is already locked.
+pub struct MachineState {
+    credit: u16,
+    tasks: TasksQueue,
+    output: String,
+    rng: StdRng,
+}
+
+impl MachineState {
+
+    pub fn get_credit(&self) -> u16 {
+        return self.credit;
+    }
+
+    /// Increases the credit. Credit won't go beyond the max of u16.
+    pub fn add_credit(&mut self, value: u16) {
+        self.credit = self.credit.saturating_add(value);
+    }
+
+    /// Appends a character to the output of the machine.
+    pub fn print(&mut self, character: char) {
+        self.output.push(character);
+    }
+
+    /// Generates a random number from the machine's own random generator.
+    pub fn random(&mut self) -> u16 {
+        return self.rng.gen();
+    }
+
+    /// Tasks that are issued for this machine.
+    pub fn get_tasks(&mut self) -> &mut TasksQueue {
+        return &mut self.tasks;
+    }
+}
+
+impl SysCallback for MachineState {
+
+    fn syscall(&mut self, cpu_state: &mut CPUState) {
+        syscalls::handle_syscall(self, cpu_state);
+    }
+}
+
 pub struct Machine {
     name: String,
     emulator: Emulator,
-    credit: u16,
+    state: Rc<Mutex<MachineState>>,
+    // Size of the shared memory that is mapped at the end of the memory.
+    shared_memory_size: u16,
 }
 
 impl Machine {
 
-    pub fn new(name: &String, memory_size: u16, initial_credit: u16) -> Rc<Mutex<Machine>> {
-        let instance = Machine {
+    /// Creates a new machine.
+    ///
+    /// @seed: Seed of the random numbers that the bot receives. If None, a
+    ///     random seed is used. Machines with the same seed receive the same
+    ///     sequence of random numbers and the same tasks.
+    pub fn new(name: &String, memory_size: u16, initial_credit: u16, seed: Option<u64>)
+        -> Rc<Mutex<Machine>> {
+
+        let (rng, tasks) = match seed {
+            Some(seed) => (StdRng::seed_from_u64(seed), TasksQueue::new_with_seed(seed)),
+            None => (StdRng::from_entropy(), TasksQueue::new()),
+        };
+
+        let state = Rc::new(Mutex::new(MachineState {
+            credit: initial_credit,
+            tasks: tasks,
+            output: String::new(),
+            rng: rng,
+        }));
+
+        let mut instance = Machine {
             name: name.clone(),
             emulator: Emulator::new(memory_size),
-            credit: initial_credit,
+            state: state,
+            shared_memory_size: 0,
         };
 
-        let rc_instance = Rc::new(Mutex::new(instance));
-
-        // Passing a weak reference of instance to the emulator.
-        let weak_instance = Rc::downgrade(&Rc::clone(&rc_instance));
-        rc_instance.lock().unwrap().emulator.register_sys_callback(weak_instance);
+        // Passing a weak reference of the state to the emulator.
+        let weak_state = Rc::downgrade(&Rc::clone(&instance.state));
+        instance.emulator.register_sys_callback(weak_state);
 
-        return rc_instance;
+        return Rc::new(Mutex::new(instance));
     }
 
     /// Gets name of the machine.
@@ -51,27 +122,100 @@ impl Machine {
         return self.name.clone();
     }
 
+    /// Gets remaining credit of the machine.
+    pub fn get_credit(&self) -> u16 {
+        return self.state.lock().unwrap().get_credit();
+    }
+
+    /// Gets everything that the bot printed so far.
+    pub fn get_output(&self) -> String {
+        return self.state.lock().unwrap().output.clone();
+    }
+
     /// Loads a bot into the machine.
     /// It loads the bot into the zero index of the memory.
     ///
     /// @file_path: Path to the file that contains bot's binary code.
     pub fn load_bot(&mut self, file_path: &String) -> Result<(), &'static str> {
-        return self.emulator.load_from_file(file_path, 0);
+        return self.load_bot_at(file_path, 0);
+    }
+
+    /// Loads a bot into the specified address of the memory. The bot starts
+    /// executing from this address.
+    ///
+    /// @file_path: Path to the file that contains bot's binary code.
+    /// @start: Memory address to load the bot into.
+    pub fn load_bot_at(&mut self, file_path: &String, start: u16) -> Result<(), &'static str> {
+        return self.emulator.load_program_from_file(file_path, start);
+    }
+
+    /// Maps the shared memory at the end of the machine's memory, so the last
+    /// words of the memory are shared with the other machines.
+    /// Returns the address that the shared memory starts from.
+    ///
+    /// Returns error if the shared memory is not smaller than the machine's memory,
+    /// or if it overlaps the loaded bot.
+    pub fn map_shared_memory(&mut self, shared_memory: &SharedMemory) -> Result<u16, &'static str> {
+        if shared_memory.size() >= self.memory_size() {
+            return Err("Shared memory should be smaller than the machine's memory.");
+        }
+
+        let start = self.memory_size() - shared_memory.size();
+        if start < self.emulator.loaded_size() {
+            return Err("Shared memory overlaps the loaded bot.");
+        }
+
+        self.emulator.map_io(start, self.memory_size(), shared_memory.handler(start))?;
+        self.shared_memory_size = shared_memory.size();
+
+        return Ok(start);
+    }
+
+    /// Returns size of the machine's memory.
+    pub fn memory_size(&self) -> u16 {
+        return self.emulator.memory_size();
+    }
+
+    /// Returns an estimate of the free memory, i.e. memory that nothing
+    /// is loaded into, and isn't shared.
+    pub fn free_memory(&self) -> u16 {
+        return (self.emulator.memory_size() - self.emulator.loaded_size())
+            .saturating_sub(self.shared_memory_size);
+    }
+
+    /// Assembles the specified source code, and loads it into the zero
+    /// index of the memory.
+    ///
+    /// @source: Assembly source code of the bot.
+    #[cfg(test)]
+    pub fn load_bot_source(&mut self, source: &str) -> Result<(), String> {
+        let assembly = compiler::assemble(source)?;
+
+        return match self.emulator.load(assembly.get_words(), 0) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(String::from(error)),
+        };
     }
 
-    /// Clocks the machine CPU.
+    /// Clocks the machine CPU, and charges the machine by cost of the executed instruction.
     /// If any error returns, it means something went really wrong and
     /// this machine is no longer in a valid state.
     pub fn clock(&mut self) -> Result<(), String> {
-        let result = self.emulator.clock();
-
-        if result.is_err() {
-            return Err(format!("Error in machine [{}]: {}", self.name, result.unwrap_err()));
+        if let Err(error) = self.emulator.clock() {
+            // Some errors know the PC they happened at. For the rest, the PC
+            // is left on the failed instruction.
+            let program_counter = match error {
+                ClockError::PcOutOfBounds(pc) => pc,
+                ClockError::ExecutingIoRegion(pc) => pc,
+                _ => self.emulator.get_program_counter(),
+            };
+            return Err(format!("Error in machine [{}]: {} at PC={}", self.name, error, program_counter));
         }
 
-        self.credit -= 1;
+        let mut state = self.state.lock().unwrap();
+        state.credit = state.credit.saturating_sub(self.emulator.last_instruction_cost());
 
-        if self.credit == 0 {
+        if state.credit == 0 {
             return Err(format!("This machine has no more credit: [{}]", self.name));
         }
 
@@ -80,9 +224,482 @@ impl Machine {
 
 }
 
-impl SysCallback for Machine {
+#[cfg(test)]
+mod tests {
 
-    fn syscall(&mut self, cpu_state: &mut CPUState) {
-        syscalls::handle_syscall(self, cpu_state);
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use std::env::temp_dir;
+    use emulator::{EndOfMemoryBehavior, TerminationReason};
+    use tasks::task::Difficulty;
+
+    #[test]
+    fn memory_accounting() {
+        let mut bot_file_path = temp_dir();
+        bot_file_path.push("test_memory_accounting_bot_d83jf72");
+        let mut bot_file = File::create(&bot_file_path).unwrap();
+        bot_file.write(&[0b01100010u8, 0b01111000u8, // SET R1 120
+                         0b01100100u8, 0b10001100u8, // SET R2 140
+                         0b00100000u8, 0b01000010u8, // ADD R1 R2
+                        ]).unwrap();
+        bot_file.flush().unwrap();
+        let bot_file_path = String::from(bot_file_path.to_str().unwrap());
+
+        let machine_mutex = Machine::new(&bot_file_path, 20, 10, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        assert_eq!(machine.memory_size(), 20);
+        assert_eq!(machine.free_memory(), 20);
+
+        machine.load_bot(&bot_file_path).unwrap();
+
+        assert_eq!(machine.memory_size(), 20);
+        assert_eq!(machine.free_memory(), 17);
+    }
+
+    #[test]
+    fn instruction_cost() {
+        let nop_machine_mutex = Machine::new(&String::from("nop_bot"), 4, 100, None);
+        let mut nop_machine = nop_machine_mutex.lock().unwrap();
+        nop_machine.load_bot_source("NOP\nNOP\n").unwrap();
+
+        let syscall_machine_mutex = Machine::new(&String::from("syscall_bot"), 4, 100, None);
+        let mut syscall_machine = syscall_machine_mutex.lock().unwrap();
+        // Get credit syscall
+        syscall_machine.load_bot_source("SET R7 5\nSYSCALL\n").unwrap();
+
+        for _ in 0..2 {
+            nop_machine.clock().unwrap();
+            syscall_machine.clock().unwrap();
+        }
+
+        assert_eq!(nop_machine.get_credit(), 98);
+        assert_eq!(syscall_machine.get_credit(), 94);
+
+        // Cost more than the remaining credit kills the machine.
+        let poor_machine_mutex = Machine::new(&String::from("poor_bot"), 4, 5, None);
+        let mut poor_machine = poor_machine_mutex.lock().unwrap();
+        poor_machine.load_bot_source("SET R7 5\nSYSCALL\n").unwrap();
+
+        poor_machine.clock().unwrap();
+
+        assert_eq!(poor_machine.clock().unwrap_err(), "This machine has no more credit: [poor_bot]");
+        assert_eq!(poor_machine.get_credit(), 0);
+    }
+
+    #[test]
+    fn load_bot_at() {
+        let mut bot_file_path = temp_dir();
+        bot_file_path.push("test_load_bot_at_bot_p2m9c41");
+        let mut bot_file = File::create(&bot_file_path).unwrap();
+        bot_file.write(&[0b01100010u8, 0b01111000u8, // SET R1 120
+                        ]).unwrap();
+        bot_file.flush().unwrap();
+        let bot_file_path = String::from(bot_file_path.to_str().unwrap());
+
+        let machine_mutex = Machine::new(&bot_file_path, 10, 10, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_at(&bot_file_path, 4).unwrap();
+        assert_eq!(machine.emulator.get_program_counter(), 4);
+
+        machine.clock().unwrap();
+        assert_eq!(machine.emulator.get_register(1), Ok(120));
+        assert_eq!(machine.emulator.get_program_counter(), 5);
+    }
+
+    #[test]
+    fn load_bot_source() {
+        let machine_mutex = Machine::new(&String::from("source_bot"), 3, 10, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source("SET R1 120\n\
+                                 SET R2 140 ; A comment\n\
+                                 ADD R1 R2\n").unwrap();
+
+        machine.emulator.set_end_of_memory_behavior(EndOfMemoryBehavior::Halt);
+        let summary = machine.emulator.run_to_completion(10);
+        assert_eq!(*summary.get_termination_reason(), TerminationReason::Halted);
+        assert_eq!(summary.get_register(2), 260);
+
+        // Errors
+        assert_eq!(machine.load_bot_source("BAD R1").is_err(), true);
+        assert_eq!(machine.load_bot_source("NOP\nNOP\nNOP\nNOP").is_err(), true);
+    }
+
+    #[test]
+    fn clock_error_has_program_counter() {
+        let machine_mutex = Machine::new(&String::from("bad_bot"), 3, 10, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source("NOP\nNOP\nDATA 65535").unwrap();
+
+        machine.clock().unwrap();
+        machine.clock().unwrap();
+        assert_eq!(machine.clock().unwrap_err(),
+                   "Error in machine [bad_bot]: Unknown instruction: [1111111111111111] at PC=2");
+    }
+
+    #[test]
+    fn pc_out_of_memory() {
+        let machine_mutex = Machine::new(&String::from("lost_bot"), 2, 10, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source("NOP\nNOP").unwrap();
+
+        machine.clock().unwrap();
+        machine.clock().unwrap();
+        assert_eq!(machine.clock().unwrap_err(),
+                   "Error in machine [lost_bot]: PC goes beyond the memory! at PC=2");
+    }
+
+    #[test]
+    fn task_done_syscall() {
+        let machine_mutex = Machine::new(&String::from("worker_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        let (task_id, expected_result) = {
+            let mut state = machine.state.lock().unwrap();
+            let task_id = state.get_tasks().get_task(Difficulty::Small).unwrap();
+            (task_id, state.get_tasks().get_issued_task(task_id).unwrap().get_expected_result())
+        };
+
+        machine.load_bot_source(&format!("LOAD_IMMEDIATE R0 {}\n\
+                                          LOAD_IMMEDIATE R1 {}\n\
+                                          SET R7 {}\n\
+                                          SYSCALL\n",
+                                         task_id, expected_result, syscalls::TASK_DONE)).unwrap();
+
+        for _ in 0..4 {
+            machine.clock().unwrap();
+        }
+
+        // Each LOAD_IMMEDIATE costs 2, and SYSCALL costs 5.
+        assert_eq!(machine.emulator.get_register(0).unwrap(), syscalls::SUCCEEDED);
+        assert_eq!(machine.get_credit(), 100 - 10 + syscalls::TASK_REWARD);
+
+        // Task is already done, so it can't be rewarded again.
+        machine.emulator.reset();
+        for _ in 0..4 {
+            machine.clock().unwrap();
+        }
+
+        assert_eq!(machine.emulator.get_register(0).unwrap(), syscalls::FAILED);
+        assert_eq!(machine.get_credit(), 100 - 20 + syscalls::TASK_REWARD);
+    }
+
+    #[test]
+    fn task_done_syscall_wrong_result() {
+        let machine_mutex = Machine::new(&String::from("lazy_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        let (task_id, expected_result) = {
+            let mut state = machine.state.lock().unwrap();
+            let task_id = state.get_tasks().get_task(Difficulty::Small).unwrap();
+            (task_id, state.get_tasks().get_issued_task(task_id).unwrap().get_expected_result())
+        };
+
+        machine.load_bot_source(&format!("LOAD_IMMEDIATE R0 {}\n\
+                                          LOAD_IMMEDIATE R1 {}\n\
+                                          SET R7 {}\n\
+                                          SYSCALL\n",
+                                         task_id, expected_result.wrapping_add(1),
+                                         syscalls::TASK_DONE)).unwrap();
+
+        for _ in 0..4 {
+            machine.clock().unwrap();
+        }
+
+        assert_eq!(machine.emulator.get_register(0).unwrap(), syscalls::FAILED);
+        assert_eq!(machine.get_credit(), 100 - 10);
+    }
+
+    #[test]
+    fn pick_a_task_syscall() {
+        let machine_mutex = Machine::new(&String::from("eager_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source(&format!("SET R7 {}\n\
+                                          SYSCALL\n",
+                                         syscalls::PICK_A_TASK)).unwrap();
+
+        machine.clock().unwrap();
+        machine.clock().unwrap();
+
+        let task_id = machine.emulator.get_register(0).unwrap();
+        assert_ne!(task_id, syscalls::NO_TASK);
+
+        let mut state = machine.state.lock().unwrap();
+        let task = state.get_tasks().get_issued_task(task_id).unwrap();
+        assert_eq!(task.get_data().len(), 3);
+        for (index, value) in task.get_data().iter().enumerate() {
+            assert_ne!(*value, 0);
+            assert_eq!(machine.emulator.get_register(index + 1).unwrap(), *value);
+        }
+    }
+
+    #[test]
+    fn pick_a_tiered_task_syscall() {
+        let expected_sizes = [(syscalls::SMALL_TASK, Difficulty::Small, 3),
+                              (syscalls::MEDIUM_TASK, Difficulty::Medium, 4),
+                              (syscalls::LARGE_TASK, Difficulty::Large, 6)];
+
+        for &(difficulty_code, difficulty, data_size) in expected_sizes.iter() {
+            let machine_mutex = Machine::new(&String::from("ambitious_bot"), 16, 100, None);
+            let mut machine = machine_mutex.lock().unwrap();
+
+            machine.load_bot_source(&format!("SET R0 {}\n\
+                                              SET R7 {}\n\
+                                              SYSCALL\n",
+                                             difficulty_code, syscalls::PICK_A_TIERED_TASK)).unwrap();
+
+            for _ in 0..3 {
+                machine.clock().unwrap();
+            }
+
+            let task_id = machine.emulator.get_register(0).unwrap();
+            assert_ne!(task_id, syscalls::NO_TASK);
+
+            let mut state = machine.state.lock().unwrap();
+            let task = state.get_tasks().get_issued_task(task_id).unwrap();
+            assert_eq!(task.get_difficulty(), difficulty);
+            assert_eq!(task.get_data().len(), data_size);
+            for (index, value) in task.get_data().iter().enumerate() {
+                assert_eq!(machine.emulator.get_register(index + 1).unwrap(), *value);
+            }
+        }
+
+        // Unknown difficulty.
+        let machine_mutex = Machine::new(&String::from("confused_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+        machine.load_bot_source(&format!("SET R0 3\n\
+                                          SET R7 {}\n\
+                                          SYSCALL\n",
+                                         syscalls::PICK_A_TIERED_TASK)).unwrap();
+        machine.clock().unwrap();
+        machine.clock().unwrap();
+        assert_eq!(machine.clock().is_err(), true);
+    }
+
+    #[test]
+    fn large_task_reward() {
+        let machine_mutex = Machine::new(&String::from("hard_worker_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        let (task_id, expected_result) = {
+            let mut state = machine.state.lock().unwrap();
+            let task_id = state.get_tasks().get_task(Difficulty::Large).unwrap();
+            (task_id, state.get_tasks().get_issued_task(task_id).unwrap().get_expected_result())
+        };
+
+        machine.load_bot_source(&format!("LOAD_IMMEDIATE R0 {}\n\
+                                          LOAD_IMMEDIATE R1 {}\n\
+                                          SET R7 {}\n\
+                                          SYSCALL\n",
+                                         task_id, expected_result, syscalls::TASK_DONE)).unwrap();
+
+        for _ in 0..4 {
+            machine.clock().unwrap();
+        }
+
+        assert_eq!(machine.emulator.get_register(0).unwrap(), syscalls::SUCCEEDED);
+        assert_eq!(machine.get_credit(), 100 - 10 + syscalls::TASK_REWARD * 4);
+    }
+
+    #[test]
+    fn print_syscall() {
+        let machine_mutex = Machine::new(&String::from("chatty_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source(&format!("SET R7 {}\n\
+                                          SET R0 72 ; H\n\
+                                          SYSCALL\n\
+                                          SET R0 105 ; i\n\
+                                          SYSCALL\n",
+                                         syscalls::PRINT)).unwrap();
+
+        assert_eq!(machine.get_output(), "");
+
+        for _ in 0..5 {
+            machine.clock().unwrap();
+        }
+
+        assert_eq!(machine.get_output(), "Hi");
+    }
+
+    #[test]
+    fn random_syscall() {
+        let source = format!("SET R7 {}\n\
+                              SYSCALL\n\
+                              COPY R0 R1\n\
+                              SYSCALL\n\
+                              COPY R0 R2\n\
+                              SYSCALL\n",
+                             syscalls::RANDOM);
+
+        let first_mutex = Machine::new(&String::from("first_lucky_bot"), 16, 100, Some(42));
+        let second_mutex = Machine::new(&String::from("second_lucky_bot"), 16, 100, Some(42));
+        let other_mutex = Machine::new(&String::from("other_lucky_bot"), 16, 100, Some(7));
+
+        let mut first = first_mutex.lock().unwrap();
+        let mut second = second_mutex.lock().unwrap();
+        let mut other = other_mutex.lock().unwrap();
+
+        for machine in [&mut first, &mut second, &mut other].iter_mut() {
+            machine.load_bot_source(&source).unwrap();
+            for _ in 0..6 {
+                machine.clock().unwrap();
+            }
+        }
+
+        assert_eq!(first.emulator.get_registers()[0..3], second.emulator.get_registers()[0..3]);
+        assert_ne!(first.emulator.get_registers()[0..3], other.emulator.get_registers()[0..3]);
+    }
+
+    #[test]
+    fn get_credit_syscall() {
+        let machine_mutex = Machine::new(&String::from("worried_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source(&format!("NOP\n\
+                                          NOP\n\
+                                          SET R7 {}\n\
+                                          SYSCALL\n",
+                                         syscalls::GET_CREDIT)).unwrap();
+
+        for _ in 0..4 {
+            machine.clock().unwrap();
+        }
+
+        // Three clocks were consumed before the SYSCALL, which costs 5 itself.
+        assert_eq!(machine.emulator.get_register(0).unwrap(), 100 - 3);
+        assert_eq!(machine.get_credit(), 100 - 8);
+    }
+
+    #[test]
+    fn call_stack_depth_syscall() {
+        let machine_mutex = Machine::new(&String::from("deep_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source(&format!("SET R1 @first\n\
+                                          SUBROUTINE R1\n\
+                                          HALT\n\
+                                          first:\n\
+                                          SET R2 @second\n\
+                                          SUBROUTINE R2\n\
+                                          RETURN\n\
+                                          second:\n\
+                                          SET R7 {}\n\
+                                          SYSCALL\n\
+                                          RETURN\n",
+                                         syscalls::CALL_STACK_DEPTH)).unwrap();
+
+        for _ in 0..6 {
+            machine.clock().unwrap();
+        }
+
+        assert_eq!(machine.emulator.get_register(0).unwrap(), 2);
+    }
+
+    /// Runs a bot that loads "value" into R0 and calls the specified sys call.
+    /// Returns the registers afterwards.
+    fn run_signed_syscall(syscall: u16, value: i16) -> [u16; 8] {
+        let machine_mutex = Machine::new(&String::from("signed_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source(&format!("LOAD_IMMEDIATE R0 {}\n\
+                                          SET R7 {}\n\
+                                          SYSCALL\n",
+                                         value as u16, syscall)).unwrap();
+
+        for _ in 0..3 {
+            machine.clock().unwrap();
+        }
+
+        return machine.emulator.get_registers();
+    }
+
+    #[test]
+    fn abs_syscall() {
+        assert_eq!(run_signed_syscall(syscalls::ABS, -5)[0], 5);
+        assert_eq!(run_signed_syscall(syscalls::ABS, 0)[0], 0);
+        assert_eq!(run_signed_syscall(syscalls::ABS, 300)[0], 300);
+        assert_eq!(run_signed_syscall(syscalls::ABS, i16::MIN)[0], i16::MAX as u16);
+    }
+
+    #[test]
+    fn sign_syscall() {
+        assert_eq!(run_signed_syscall(syscalls::SIGN, -5)[1], -1i16 as u16);
+        assert_eq!(run_signed_syscall(syscalls::SIGN, i16::MIN)[1], -1i16 as u16);
+        assert_eq!(run_signed_syscall(syscalls::SIGN, 0)[1], 0);
+        assert_eq!(run_signed_syscall(syscalls::SIGN, 300)[1], 1);
+    }
+
+    #[test]
+    fn shared_memory() {
+        let shared_memory = SharedMemory::new(4);
+
+        let writer_mutex = Machine::new(&String::from("writer_bot"), 16, 100, None);
+        let reader_mutex = Machine::new(&String::from("reader_bot"), 20, 100, None);
+        let mut writer = writer_mutex.lock().unwrap();
+        let mut reader = reader_mutex.lock().unwrap();
+
+        assert_eq!(writer.map_shared_memory(&shared_memory).unwrap(), 12);
+        assert_eq!(reader.map_shared_memory(&shared_memory).unwrap(), 16);
+
+        // Writes to the second word of the shared memory.
+        writer.load_bot_source("SET R1 13\n\
+                                SET R2 300\n\
+                                COPY R2 M1\n").unwrap();
+        // Reads the second word of the shared memory.
+        reader.load_bot_source("SET R1 17\n\
+                                COPY M1 R2\n").unwrap();
+
+        for _ in 0..3 {
+            writer.clock().unwrap();
+        }
+        reader.clock().unwrap();
+        reader.clock().unwrap();
+
+        assert_eq!(reader.emulator.get_register(2).unwrap(), 300);
+
+        // Errors
+        let large_shared_memory = SharedMemory::new(16);
+        assert_eq!(writer.map_shared_memory(&large_shared_memory).is_err(), true);
+    }
+
+    #[test]
+    fn shared_memory_overlapping_bot() {
+        let shared_memory = SharedMemory::new(4);
+
+        let machine_mutex = Machine::new(&String::from("long_bot"), 8, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        // Bot's code reaches the last four words.
+        machine.load_bot_source("SET R1 1\n\
+                                 SET R2 2\n\
+                                 SET R3 3\n\
+                                 SET R4 4\n\
+                                 SET R5 5\n").unwrap();
+
+        assert_eq!(machine.map_shared_memory(&shared_memory),
+                   Err("Shared memory overlaps the loaded bot."));
+        assert_eq!(machine.free_memory(), 3);
+    }
+
+    #[test]
+    fn free_memory_with_shared_memory() {
+        let shared_memory = SharedMemory::new(4);
+
+        let machine_mutex = Machine::new(&String::from("short_bot"), 16, 100, None);
+        let mut machine = machine_mutex.lock().unwrap();
+
+        machine.load_bot_source("SET R1 1\n\
+                                 SET R2 2\n").unwrap();
+        assert_eq!(machine.free_memory(), 14);
+
+        assert_eq!(machine.map_shared_memory(&shared_memory), Ok(12));
+        assert_eq!(machine.free_memory(), 10);
     }
 }
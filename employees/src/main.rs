@@ -20,8 +20,10 @@ extern crate simplelog;
 
 extern crate rand;
 extern crate emulator;
+extern crate compiler;
 
 mod machine;
+mod shared_memory;
 mod syscalls;
 mod tasks;
 
@@ -29,64 +31,299 @@ use std::env;
 use std::sync::Mutex;
 use std::rc::Rc;
 use std::process;
+use std::fs;
+use std::io;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use shared_memory::SharedMemory;
+
+
+/// Returns memory size that the bot needs.
+/// It reads the metadata file that compiler writes next to the bot's binary
+/// file (bot_path.meta). If there's no metadata, returns the default size.
+///
+/// @bot: Path to binary file of the bot.
+/// @default_memory: Memory size to use if bot didn't request any.
+fn bot_memory_size(bot: &String, default_memory: u16) -> Result<u16, String> {
+    let metadata_path = format!("{}.meta", bot);
+
+    let metadata = match fs::read_to_string(&metadata_path) {
+        Ok(content) => content,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(default_memory),
+        Err(error) => return Err(format!("Could not read [{}]: {}", metadata_path, error)),
+    };
+
+    for line in metadata.lines() {
+        let setting: Vec<&str> = line.trim().splitn(2, '=').collect();
+        if setting.len() == 2 && setting[0] == "memory" {
+            return match setting[1].parse::<u16>() {
+                Ok(0) => Err(format!("Memory size in [{}] should be more than zero.", metadata_path)),
+                Ok(v) => Ok(v),
+                Err(error) => Err(format!("Invalid memory size in [{}]: {}", metadata_path, error)),
+            };
+        }
+    }
+
+    return Ok(default_memory);
+}
+
+/// Initial settings of the game.
+struct Config {
+    // Initial memory of each bot's machine, if bot didn't request any.
+    initial_memory: u16,
+    initial_credit: u16,
+    // If more than one bot is still alive after this number of rounds, game
+    // finishes with a draw.
+    max_rounds: u32,
+    // Seed of the random numbers of the game, i.e. order of the bots. If None,
+    // a random seed is used.
+    seed: Option<u64>,
+    // Size of the memory that is shared between all the bots. It's mapped at
+    // the end of each bot's memory. Zero means no shared memory.
+    shared_memory: u16,
+}
+
+impl Config {
+
+    fn default() -> Config {
+        Config {
+            initial_memory: DEFAULT_INITIAL_MEMORY,
+            initial_credit: DEFAULT_INITIAL_CREDIT,
+            max_rounds: DEFAULT_MAX_ROUNDS,
+            seed: None,
+            shared_memory: 0,
+        }
+    }
+}
+
+/// Reads the config file. Config file contains `key = value` lines, i.e.
+/// `initial_credit = 80`. Lines that start with `#` are comments. Settings that
+/// are not in the file keep their default values. If the file doesn't exist,
+/// all settings are default.
+///
+/// @path: Path to the config file.
+fn read_config(path: &str) -> Result<Config, String> {
+    let mut config = Config::default();
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(config),
+        Err(error) => return Err(format!("Could not read [{}]: {}", path, error)),
+    };
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#") {
+            continue;
+        }
+
+        let setting: Vec<&str> = line.splitn(2, '=').map(|part| part.trim()).collect();
+        if setting.len() != 2 {
+            return Err(format!("Invalid line in [{}]: [{}]", path, line));
+        }
+
+        let invalid_value = |error: ::std::num::ParseIntError| {
+            format!("Invalid value of [{}] in [{}]: {}", setting[0], path, error)
+        };
+
+        match setting[0] {
+            "initial_memory" => config.initial_memory = setting[1].parse::<u16>().map_err(invalid_value)?,
+            "initial_credit" => config.initial_credit = setting[1].parse::<u16>().map_err(invalid_value)?,
+            "max_rounds" => config.max_rounds = setting[1].parse::<u32>().map_err(invalid_value)?,
+            "seed" => config.seed = Some(setting[1].parse::<u64>().map_err(invalid_value)?),
+            "shared_memory" => config.shared_memory = setting[1].parse::<u16>().map_err(invalid_value)?,
+            _ => return Err(format!("Unknown setting in [{}]: [{}]", path, setting[0])),
+        }
+    }
 
+    return Ok(config);
+}
 
 /// Starts the game.
 ///
 /// @bots: List of paths to binary files of bots.
-/// @initial_memory: Initial memory for each bot's machine.
-/// @initial_credit: Initial credit for each bot.
-fn start(bots: &Vec<String>, initial_memory: u16, initial_credit: u16) {
+/// @config: Initial settings of the game.
+/// @rounds: If set, game runs exactly this number of rounds (each bot is clocked
+///     once per round), and then the remaining bots are scored by their credit.
+///     Otherwise, game runs until at most one bot remains alive, or max_rounds
+///     of the config is reached.
+///
+/// Returns name and credit of the bots that are alive at the end of the game.
+fn start(bots: &Vec<String>, config: &Config, rounds: Option<u32>) -> Vec<(String, u16)> {
+
+    let max_rounds = config.max_rounds;
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
 
     let mut machines: Vec<Rc<Mutex<machine::Machine>>> = Vec::with_capacity(bots.len());
+    let shared_memory = SharedMemory::new(config.shared_memory);
 
     // Creating a machine for each bot.
     for bot in bots.iter() {
+        let memory_size = bot_memory_size(bot, config.initial_memory)
+            .expect("Could not read bot's metadata.");
+        // With a seeded game, random numbers of the machines are reproducible too.
+        let machine_seed = match config.seed {
+            Some(_) => Some(rng.gen()),
+            None => None,
+        };
         let bot_machine =
-            machine::Machine::new(bot, initial_memory, initial_credit);
-        bot_machine.lock().unwrap().load_bot(bot).
-            expect("Could not load bot.");
+            machine::Machine::new(bot, memory_size, config.initial_credit, machine_seed);
+        {
+            let mut locked_machine = bot_machine.lock().unwrap();
+            locked_machine.load_bot(bot).expect("Could not load bot.");
+            if config.shared_memory > 0 {
+                let start = locked_machine.map_shared_memory(&shared_memory)
+                    .expect("Could not map the shared memory.");
+                info!("Bot [{}] has the shared memory at [{}]", bot, start);
+            }
+            info!("Bot [{}] loaded. Memory size: [{}] Free memory: [{}]",
+                  bot, locked_machine.memory_size(), locked_machine.free_memory());
+        }
 
         machines.push(bot_machine);
     }
 
+    let mut round: u32 = 0;
+
     // Main loop
     loop {
         // Keeps index of bots that should be removed from the list (dead bots).
         let mut bots_to_remove: Vec<usize> = Vec::new();
+        // Name and credit of the bots that died in this round.
+        let mut dead_bots: Vec<(String, u16)> = Vec::new();
 
-        for (index, bot_machine_mutex) in machines.iter().enumerate() {
-            let mut bot_machine = bot_machine_mutex.lock().unwrap();
+        for index in round_order(&mut rng, machines.len()) {
+            let mut bot_machine = machines[index].lock().unwrap();
             let result = bot_machine.clock();
             if result.is_err() {
                 error!("{}", result.unwrap_err());
                 error!("let it die.");
+                log_output(&bot_machine);
                 bots_to_remove.push(index);
+                dead_bots.push((bot_machine.get_name(), bot_machine.get_credit()));
             }
         }
 
         if !bots_to_remove.is_empty() {
             // Removing dead bots.
             // We iterates in reverse order, because "remove" will
-            // change indexes.
+            // change indexes. Bots weren't clocked in order, so indexes are sorted first.
+            bots_to_remove.sort();
             for index in bots_to_remove.iter().rev() {
                 machines.remove(*index);
             }
         }
 
+        round += 1;
+
         if machines.is_empty() {
             info!("No bot remained alive!");
+            // The ones that died last are scored.
+            score_by_credit(&dead_bots, round);
             break;
         }
 
-        if machines.len() == 1 {
-            info!("Only one bot remained alive! Our lucky winner: [{}]",
-                     machines.get(0).unwrap().lock().unwrap().get_name());
-            break;
+        match rounds {
+            Some(fixed_rounds) => {
+                if round >= fixed_rounds {
+                    score_by_credit(&scores(&machines), round);
+                    break;
+                }
+            },
+            None => {
+                if machines.len() == 1 {
+                    info!("Only one bot remained alive! Our lucky winner: [{}]",
+                          machines.get(0).unwrap().lock().unwrap().get_name());
+                    break;
+                }
+
+                if round >= max_rounds {
+                    let names: Vec<String> = machines.iter()
+                        .map(|bot_machine_mutex| bot_machine_mutex.lock().unwrap().get_name())
+                        .collect();
+                    info!("Reached the maximum of [{}] rounds. It's a draw between: [{}]",
+                          max_rounds, names.join("], ["));
+                    score_by_credit(&scores(&machines), round);
+                    break;
+                }
+            },
+        }
+    }
+
+    for bot_machine_mutex in machines.iter() {
+        log_output(&bot_machine_mutex.lock().unwrap());
+    }
+
+    return scores(&machines);
+}
+
+/// Returns the order in which the bots are clocked in a round. Bots are shuffled
+/// every round, so no bot has the advantage of always being the first.
+///
+/// @count: Number of the bots.
+fn round_order<R: Rng>(rng: &mut R, count: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..count).collect();
+    order.shuffle(rng);
+    return order;
+}
+
+/// Returns name and credit of the bots.
+fn scores(machines: &Vec<Rc<Mutex<machine::Machine>>>) -> Vec<(String, u16)> {
+    return machines.iter().map(|bot_machine_mutex| {
+        let bot_machine = bot_machine_mutex.lock().unwrap();
+        (bot_machine.get_name(), bot_machine.get_credit())
+    }).collect();
+}
+
+/// Logs whatever the bot printed, if anything.
+fn log_output(bot_machine: &machine::Machine) {
+    let output = bot_machine.get_output();
+    if !output.is_empty() {
+        info!("Output of [{}]: {}", bot_machine.get_name(), output);
+    }
+}
+
+/// Logs credit of each bot, and declares the ones with the highest credit as winners.
+///
+/// @scores: Name and credit of the bots.
+/// @round: Number of rounds that are played.
+fn score_by_credit(scores: &Vec<(String, u16)>, round: u32) {
+    info!("Game finished after [{}] rounds. Scores:", round);
+
+    let mut highest_credit: u16 = 0;
+    for &(ref name, credit) in scores.iter() {
+        info!("[{}]: [{}]", name, credit);
+
+        if credit > highest_credit {
+            highest_credit = credit;
         }
     }
+
+    let winners: Vec<String> = scores.iter()
+        .filter(|&&(_, credit)| credit == highest_credit)
+        .map(|&(ref name, _)| name.clone())
+        .collect();
+
+    if winners.len() == 1 {
+        info!("Winner by credit: [{}]", winners[0]);
+    } else {
+        info!("Shared win by credit: [{}]", winners.join("], ["));
+    }
 }
 
+/// Path of the config file, relative to the current directory.
+const CONFIG_FILE: &str = "sillyverse.toml";
+
+const DEFAULT_INITIAL_MEMORY: u16 = 128;
+const DEFAULT_INITIAL_CREDIT: u16 = 80;
+/// Maximum number of rounds, after which the game finishes with a draw.
+const DEFAULT_MAX_ROUNDS: u32 = 1000000;
+
 fn main() {
     let mut args: Vec<String> = env::args().collect();
     // First arg is the binary itself.
@@ -103,8 +340,15 @@ fn main() {
     simplelog::TermLogger::init(simplelog::LevelFilter::Info, simplelog::Config::default(), simplelog::TerminalMode::Mixed)
         .unwrap();
 
-    //TODO: Read initial values from config file.
-    start(&args, 128, 80);
+    let config = match read_config(CONFIG_FILE) {
+        Ok(config) => config,
+        Err(error) => {
+            error!("{}", error);
+            process::exit(1);
+        },
+    };
+
+    start(&args, &config, None);
 
     info!("The game finished.");
 }
@@ -117,15 +361,16 @@ mod tests {
     use std::io::Write;
     use std::io;
     use std::env::temp_dir;
-    use std::sync::{Mutex, Arc};
+    use std::sync::{Mutex, Once};
 
-    struct MockLogger {
-        logs_buffer: Arc<Mutex<Vec<u8>>>,
-    }
+    static LOGGER_INIT: Once = Once::new();
+    static LOGS_BUFFER: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+    struct MockLogger {}
 
     impl Write for MockLogger {
         fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-            self.logs_buffer.lock().unwrap().extend_from_slice(buf);
+            LOGS_BUFFER.lock().unwrap().extend_from_slice(buf);
             return Ok(buf.len());
         }
 
@@ -134,6 +379,87 @@ mod tests {
         }
     }
 
+    /// Initializes the logger in a way we can check the output later. Logger can
+    /// only be initialized once, so all the tests share it.
+    fn init_test_logger() {
+        LOGGER_INIT.call_once(|| {
+            simplelog::WriteLogger::init(simplelog::LevelFilter::Info,
+                                         simplelog::Config::default(),
+                                         MockLogger {}).unwrap();
+        });
+    }
+
+    /// Returns everything that is logged so far.
+    fn test_logs() -> String {
+        return String::from_utf8(LOGS_BUFFER.lock().unwrap().clone()).unwrap();
+    }
+
+    #[test]
+    fn bot_memory_size_from_metadata() {
+        let mut bot_file_path = temp_dir();
+        bot_file_path.push("test_bot_memory_size_j8d7f2k");
+        let bot_file_path = String::from(bot_file_path.to_str().unwrap());
+
+        let mut bot_file = File::create(&bot_file_path).unwrap();
+        bot_file.write(&[0u8, 0u8]).unwrap();
+        bot_file.flush().unwrap();
+
+        // No metadata.
+        let _ = fs::remove_file(format!("{}.meta", bot_file_path));
+        assert_eq!(bot_memory_size(&bot_file_path, 128).unwrap(), 128);
+
+        let mut metadata_file = File::create(format!("{}.meta", bot_file_path)).unwrap();
+        metadata_file.write(b"memory=256\n").unwrap();
+        metadata_file.flush().unwrap();
+
+        let memory_size = bot_memory_size(&bot_file_path, 128).unwrap();
+        assert_eq!(memory_size, 256);
+
+        let bot_machine = machine::Machine::new(&bot_file_path, memory_size, 10, None);
+        assert_eq!(bot_machine.lock().unwrap().memory_size(), 256);
+
+        // Bad metadata.
+        let mut metadata_file = File::create(format!("{}.meta", bot_file_path)).unwrap();
+        metadata_file.write(b"memory=abc\n").unwrap();
+        metadata_file.flush().unwrap();
+
+        assert_eq!(bot_memory_size(&bot_file_path, 128).is_err(), true);
+
+        // Zero memory.
+        let mut metadata_file = File::create(format!("{}.meta", bot_file_path)).unwrap();
+        metadata_file.write(b"memory=0\n").unwrap();
+        metadata_file.flush().unwrap();
+
+        assert_eq!(bot_memory_size(&bot_file_path, 128).unwrap_err(),
+                   format!("Memory size in [{}.meta] should be more than zero.", bot_file_path));
+    }
+
+    #[test]
+    fn fixed_rounds() {
+        let mut bot_paths: Vec<String> = Vec::new();
+
+        for i in 0..3 {
+            let mut bot_file_path = temp_dir();
+            bot_file_path.push(format!("test_fixed_rounds_bot_{}_k2j8s7d", i));
+            let mut bot_file = File::create(&bot_file_path).unwrap();
+            bot_file.write(&[0b00000000u8, 0b01000000u8, // JUMP R0 (zero)
+                            ]).unwrap();
+            bot_file.flush().unwrap();
+
+            bot_paths.push(String::from(bot_file_path.to_str().unwrap()));
+        }
+
+        // All bots loop forever, but the game should finish after 5 rounds.
+        let config = Config { initial_memory: 10, initial_credit: 100, ..Config::default() };
+        let scores = start(&bot_paths, &config, Some(5));
+
+        assert_eq!(scores.len(), 3);
+        for (index, score) in scores.iter().enumerate() {
+            assert_eq!(score.0, bot_paths[index]);
+            assert_eq!(score.1, 95);
+        }
+    }
+
     #[test]
     fn two_bots() {
         let mut first_bot_file_path = temp_dir();
@@ -149,31 +475,185 @@ mod tests {
         second_bot_file_path.push("test_second_bot_fju8734");
         let mut second_bot_file = File::create(&second_bot_file_path).unwrap();
         second_bot_file.write(&[0b00000000u8, 0b00000000u8, // NOP
-                                     0b11110011u8, 0b11111111u8, // Bad instruction
+                                     0b11111111u8, 0b11111111u8, // Bad instruction
                                      ]).unwrap();
         second_bot_file.flush().unwrap();
 
         let first_bot_file_path = String::from(first_bot_file_path.to_str().unwrap());
         let second_bot_file_path = String::from(second_bot_file_path.to_str().unwrap());
 
-        // Initializing the logger in a way we can check the output later.
-        let logs_buffer = Arc::new(Mutex::new(Vec::new()));
-        let mock_logger = MockLogger {
-            logs_buffer: Arc::clone(&logs_buffer),
-        };
+        init_test_logger();
 
-        simplelog::WriteLogger::init(simplelog::LevelFilter::Info,
-                                     simplelog::Config::default(),
-                                     mock_logger).unwrap();
+        let config = Config { initial_memory: 20, initial_credit: 3, ..Config::default() };
+        start(&vec![first_bot_file_path.clone(), second_bot_file_path.clone()], &config, None);
 
-        start(&vec![first_bot_file_path.clone(), second_bot_file_path.clone()],
-              20, 3);
-
-        let expected_log_1 = format!("Error in machine [{}]: Unknown instruction: [1111001111111111]", second_bot_file_path);
+        let expected_log_1 = format!("Error in machine [{}]: Unknown instruction: [1111111111111111]", second_bot_file_path);
         let expected_log_2 = format!("Only one bot remained alive! Our lucky winner: [{}]", first_bot_file_path);
 
-        let logs = String::from_utf8(logs_buffer.lock().unwrap().clone()).unwrap();
+        let logs = test_logs();
         assert!(logs.contains(expected_log_1.as_str()));
         assert!(logs.contains(expected_log_2.as_str()))
     }
+
+    #[test]
+    fn max_rounds_draw() {
+        let mut bot_paths: Vec<String> = Vec::new();
+
+        for i in 0..2 {
+            let mut bot_file_path = temp_dir();
+            bot_file_path.push(format!("test_max_rounds_draw_bot_{}_d8s7c2m", i));
+            let mut bot_file = File::create(&bot_file_path).unwrap();
+            bot_file.write(&[0b00000000u8, 0b01000000u8, // JUMP R0 (zero)
+                            ]).unwrap();
+            bot_file.flush().unwrap();
+
+            bot_paths.push(String::from(bot_file_path.to_str().unwrap()));
+        }
+
+        init_test_logger();
+
+        // Both bots loop forever, and they have enough credit to outlive the limit.
+        let config = Config { initial_memory: 10, initial_credit: 1000, max_rounds: 20, ..Config::default() };
+        let scores = start(&bot_paths, &config, None);
+
+        assert_eq!(scores.len(), 2);
+        for score in scores.iter() {
+            assert_eq!(score.1, 980);
+        }
+
+        let expected_log = format!("Reached the maximum of [20] rounds. It's a draw between: [{}], [{}]",
+                                   bot_paths[0], bot_paths[1]);
+        let logs = test_logs();
+        assert!(logs.contains(expected_log.as_str()));
+    }
+
+    #[test]
+    fn config_file() {
+        let mut config_path = temp_dir();
+        config_path.push("test_config_file_m2k9d7s.toml");
+        let config_path = String::from(config_path.to_str().unwrap());
+
+        // No config file.
+        let _ = fs::remove_file(&config_path);
+        let config = read_config(&config_path).unwrap();
+        assert_eq!(config.initial_memory, DEFAULT_INITIAL_MEMORY);
+        assert_eq!(config.initial_credit, DEFAULT_INITIAL_CREDIT);
+        assert_eq!(config.max_rounds, DEFAULT_MAX_ROUNDS);
+        assert_eq!(config.seed, None);
+
+        let mut config_file = File::create(&config_path).unwrap();
+        config_file.write(b"# Game settings\n\
+                            initial_memory = 48\n\
+                            \n\
+                            max_rounds=3\n\
+                            seed = 42\n\
+                            shared_memory = 8\n").unwrap();
+        config_file.flush().unwrap();
+
+        let config = read_config(&config_path).unwrap();
+        assert_eq!(config.initial_memory, 48);
+        assert_eq!(config.initial_credit, DEFAULT_INITIAL_CREDIT);
+        assert_eq!(config.max_rounds, 3);
+        assert_eq!(config.seed, Some(42));
+        assert_eq!(config.shared_memory, 8);
+
+        let mut bot_file_path = temp_dir();
+        bot_file_path.push("test_config_file_bot_x83kd0a");
+        let mut bot_file = File::create(&bot_file_path).unwrap();
+        bot_file.write(&[0b00000000u8, 0b01000000u8, // JUMP R0 (zero)
+                        ]).unwrap();
+        bot_file.flush().unwrap();
+        let bot_file_path = String::from(bot_file_path.to_str().unwrap());
+        let _ = fs::remove_file(format!("{}.meta", bot_file_path));
+
+        init_test_logger();
+
+        let scores = start(&vec![bot_file_path.clone(), bot_file_path.clone()], &config, None);
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[0].1, DEFAULT_INITIAL_CREDIT - 3);
+
+        let expected_log = format!("Bot [{}] loaded. Memory size: [48]", bot_file_path);
+        assert!(test_logs().contains(expected_log.as_str()));
+
+        // Bad configs.
+        let mut config_file = File::create(&config_path).unwrap();
+        config_file.write(b"initial_memory = lots\n").unwrap();
+        config_file.flush().unwrap();
+        assert_eq!(read_config(&config_path).is_err(), true);
+
+        let mut config_file = File::create(&config_path).unwrap();
+        config_file.write(b"initial_wisdom = 10\n").unwrap();
+        config_file.flush().unwrap();
+        assert_eq!(read_config(&config_path).is_err(), true);
+    }
+
+    #[test]
+    fn draw_winner_by_credit() {
+        // This bot does a task, so it earns some credit.
+        let rich_bot = compiler::assemble("SET R7 1 ; PICK_A_TASK\n\
+                                           SYSCALL\n\
+                                           ADD R1 R2\n\
+                                           ADD R1 R2\n\
+                                           ADD R1 R2\n\
+                                           ADD R3 R3\n\
+                                           SUBTRACT R2 R3\n\
+                                           COPY R3 R1\n\
+                                           SET R7 2 ; TASK_DONE\n\
+                                           SYSCALL\n\
+                                           SET R6 @loop\n\
+                                           loop:\n\
+                                           JUMP R6\n").unwrap();
+
+        let mut rich_bot_path = temp_dir();
+        rich_bot_path.push("test_draw_rich_bot_p2m8x6c");
+        let mut rich_bot_file = File::create(&rich_bot_path).unwrap();
+        for word in rich_bot.get_words().iter() {
+            rich_bot_file.write(&[(word >> 8) as u8, *word as u8]).unwrap();
+        }
+        rich_bot_file.flush().unwrap();
+
+        let mut poor_bot_path = temp_dir();
+        poor_bot_path.push("test_draw_poor_bot_q9w3e7r");
+        let mut poor_bot_file = File::create(&poor_bot_path).unwrap();
+        poor_bot_file.write(&[0b00000000u8, 0b01000000u8, // JUMP R0 (zero)
+                             ]).unwrap();
+        poor_bot_file.flush().unwrap();
+
+        let rich_bot_path = String::from(rich_bot_path.to_str().unwrap());
+        let poor_bot_path = String::from(poor_bot_path.to_str().unwrap());
+        let _ = fs::remove_file(format!("{}.meta", rich_bot_path));
+        let _ = fs::remove_file(format!("{}.meta", poor_bot_path));
+
+        init_test_logger();
+
+        let config = Config { initial_memory: 20, initial_credit: 100, max_rounds: 20, ..Config::default() };
+        let scores = start(&vec![poor_bot_path.clone(), rich_bot_path.clone()], &config, None);
+        // Rich bot did two sys calls, which cost 4 more than the other instructions.
+        assert_eq!(scores, vec![(poor_bot_path.clone(), 80), (rich_bot_path.clone(), 92)]);
+
+        let expected_log = format!("Winner by credit: [{}]", rich_bot_path);
+        assert!(test_logs().contains(expected_log.as_str()));
+    }
+
+    #[test]
+    fn shuffled_round_order() {
+        let mut first_rng = StdRng::seed_from_u64(1234);
+        let mut second_rng = StdRng::seed_from_u64(1234);
+
+        let mut orders: Vec<Vec<usize>> = Vec::new();
+        for _ in 0..10 {
+            let order = round_order(&mut first_rng, 5);
+            assert_eq!(order, round_order(&mut second_rng, 5));
+
+            // Every bot is clocked exactly once.
+            let mut sorted_order = order.clone();
+            sorted_order.sort();
+            assert_eq!(sorted_order, vec![0, 1, 2, 3, 4]);
+
+            orders.push(order);
+        }
+
+        // Order changes between rounds.
+        assert_eq!(orders.iter().all(|order| *order == orders[0]), false);
+    }
 }
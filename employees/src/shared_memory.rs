@@ -0,0 +1,74 @@
+// This file is part of Sillyverse.
+// Copyright (C) 2017-2020, Aidin Gharibnavaz <aidin@aidinhut.com>
+//
+// Sillyverse is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Sillyverse is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
+
+/// Memory that is shared between machines, so bots can see (and overwrite) each
+/// other's data.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use emulator::IoHandler;
+
+
+/// A block of memory that many machines can map. Cloning it doesn't copy the
+/// memory: all the clones point to the same block.
+#[derive(Clone)]
+pub struct SharedMemory {
+    memory: Rc<RefCell<Vec<u16>>>,
+}
+
+impl SharedMemory {
+
+    /// Creates a zero-filled shared memory.
+    ///
+    /// @size: Number of the words.
+    pub fn new(size: u16) -> SharedMemory {
+        SharedMemory {
+            memory: Rc::new(RefCell::new(vec![0u16; size as usize])),
+        }
+    }
+
+    pub fn size(&self) -> u16 {
+        return self.memory.borrow().len() as u16;
+    }
+
+    /// Creates a handler that maps this memory into a machine's memory.
+    ///
+    /// @start: Address of the machine's memory that the first word of the
+    ///     shared memory is mapped to.
+    pub fn handler(&self, start: u16) -> Box<dyn IoHandler> {
+        return Box::new(SharedMemoryHandler {
+            memory: self.clone(),
+            start: start,
+        });
+    }
+}
+
+/// Redirects reads and writes of a machine to the shared memory.
+struct SharedMemoryHandler {
+    memory: SharedMemory,
+    start: u16,
+}
+
+impl IoHandler for SharedMemoryHandler {
+
+    fn read(&mut self, address: u16) -> u16 {
+        return self.memory.memory.borrow()[(address - self.start) as usize];
+    }
+
+    fn write(&mut self, address: u16, value: u16) {
+        self.memory.memory.borrow_mut()[(address - self.start) as usize] = value;
+    }
+}
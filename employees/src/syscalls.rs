@@ -15,19 +15,164 @@
 // along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
 
 /// This file contains sys call functions.
+///
+/// Number of the sys call should be set in R7 before calling SYSCALL. Other
+/// registers are used by each sys call as described in its constant.
 
 use emulator::CPUState;
-use machine::Machine;
+use machine::MachineState;
+use tasks::task::Difficulty;
 
 
-pub fn handle_syscall(machine: &mut Machine, cpu_state: &mut CPUState) {
+/// Register that holds number of the requested sys call.
+const SYSCALL_NUMBER_REGISTER: usize = 7;
 
+/// Picks a new task. ID of the task is written in R0, and its input data
+/// in R1, R2 and R3. R0 will be NO_TASK if no more tasks can be picked.
+pub const PICK_A_TASK: u16 = 1;
+
+/// Submits result of a task. R0 should be the ID of the task, and R1 its result.
+/// If the result is correct, machine receives TASK_REWARD credits for a small
+/// task, twice of it for a medium task, and four times of it for a large task.
+/// R0 will be SUCCEEDED if the result was accepted, FAILED otherwise.
+pub const TASK_DONE: u16 = 2;
+
+/// Prints a character. R0 should be the character code, i.e. 72 for 'H'.
+pub const PRINT: u16 = 3;
+
+/// Writes a random number in R0.
+pub const RANDOM: u16 = 4;
+
+/// Writes remaining credit of the machine in R0. Clock of the SYSCALL itself
+/// is not deducted yet.
+pub const GET_CREDIT: u16 = 5;
+
+/// Writes number of the return addresses that are in the call stack in R0.
+/// Bots can check it before calling a SUBROUTINE, to avoid overflowing the stack.
+pub const CALL_STACK_DEPTH: u16 = 6;
+
+/// Interprets R0 as a signed number, and writes its absolute value back in R0.
+/// Absolute value of -32768 doesn't fit, so it becomes 32767.
+pub const ABS: u16 = 7;
+
+/// Interprets R0 as a signed number, and writes its sign in R1: -1 for
+/// negative numbers, 0 for zero, and 1 for positive numbers.
+pub const SIGN: u16 = 8;
+
+/// Same as PICK_A_TASK, but R0 should be the difficulty of the task: SMALL_TASK,
+/// MEDIUM_TASK or LARGE_TASK. Harder tasks have more input data, in R1 to R6.
+pub const PICK_A_TIERED_TASK: u16 = 9;
+
+/// Credit that a machine receives for each task that is done correctly.
+pub const TASK_REWARD: u16 = 20;
+
+pub const SUCCEEDED: u16 = 1;
+pub const FAILED: u16 = 0;
+pub const NO_TASK: u16 = 0;
+
+pub const SMALL_TASK: u16 = 0;
+pub const MEDIUM_TASK: u16 = 1;
+pub const LARGE_TASK: u16 = 2;
+
+/// Task data is written in R1 to R6 (R0 is the ID).
+const MAX_TASK_DATA: usize = 6;
+
+
+pub fn handle_syscall(machine: &mut MachineState, cpu_state: &mut CPUState) {
+
+    match cpu_state.get_register(SYSCALL_NUMBER_REGISTER) {
+        PICK_A_TASK => pick_a_task(machine, cpu_state, Difficulty::Small),
+        TASK_DONE => task_done(machine, cpu_state),
+        PRINT => print(machine, cpu_state),
+        RANDOM => random(machine, cpu_state),
+        GET_CREDIT => get_credit(machine, cpu_state),
+        CALL_STACK_DEPTH => call_stack_depth(cpu_state),
+        ABS => abs(cpu_state),
+        SIGN => sign(cpu_state),
+        PICK_A_TIERED_TASK => pick_a_tiered_task(machine, cpu_state),
+        _ => cpu_state.set_error_flag(true),
+    }
 }
 
-fn pick_a_task(machine: &mut Machine, cpu_state: &mut CPUState) {
+fn pick_a_task(machine: &mut MachineState, cpu_state: &mut CPUState, difficulty: Difficulty) {
+    let tasks = machine.get_tasks();
 
+    let task = match tasks.get_task(difficulty) {
+        Some(id) => tasks.get_issued_task(id).unwrap(),
+        None => {
+            cpu_state.set_register(0, NO_TASK);
+            return;
+        },
+    };
+
+    cpu_state.set_register(0, task.get_id());
+    for (index, value) in task.get_data().iter().take(MAX_TASK_DATA).enumerate() {
+        cpu_state.set_register(index + 1, *value);
+    }
 }
 
-fn task_done(machine: &mut Machine, cpu_state: &mut CPUState) {
+fn pick_a_tiered_task(machine: &mut MachineState, cpu_state: &mut CPUState) {
+    let difficulty = match cpu_state.get_register(0) {
+        SMALL_TASK => Difficulty::Small,
+        MEDIUM_TASK => Difficulty::Medium,
+        LARGE_TASK => Difficulty::Large,
+        _ => {
+            cpu_state.set_error_flag(true);
+            return;
+        },
+    };
 
-}
\ No newline at end of file
+    pick_a_task(machine, cpu_state, difficulty);
+}
+
+fn task_done(machine: &mut MachineState, cpu_state: &mut CPUState) {
+    let task_id = cpu_state.get_register(0);
+    let result = cpu_state.get_register(1);
+
+    let reward = match machine.get_tasks().get_issued_task(task_id) {
+        Some(task) => match task.get_difficulty() {
+            Difficulty::Small => TASK_REWARD,
+            Difficulty::Medium => TASK_REWARD * 2,
+            Difficulty::Large => TASK_REWARD * 4,
+        },
+        None => 0,
+    };
+
+    if machine.get_tasks().complete_task(task_id, result) {
+        machine.add_credit(reward);
+        cpu_state.set_register(0, SUCCEEDED);
+    } else {
+        cpu_state.set_register(0, FAILED);
+    }
+}
+
+fn print(machine: &mut MachineState, cpu_state: &mut CPUState) {
+    match char::from_u32(cpu_state.get_register(0) as u32) {
+        Some(character) => machine.print(character),
+        // Not a valid character.
+        None => cpu_state.set_error_flag(true),
+    }
+}
+
+fn random(machine: &mut MachineState, cpu_state: &mut CPUState) {
+    cpu_state.set_register(0, machine.random());
+}
+
+fn get_credit(machine: &mut MachineState, cpu_state: &mut CPUState) {
+    cpu_state.set_register(0, machine.get_credit());
+}
+
+fn call_stack_depth(cpu_state: &mut CPUState) {
+    let depth = cpu_state.get_call_stack_depth();
+    cpu_state.set_register(0, depth);
+}
+
+fn abs(cpu_state: &mut CPUState) {
+    let value = cpu_state.get_register(0) as i16;
+    cpu_state.set_register(0, value.saturating_abs() as u16);
+}
+
+fn sign(cpu_state: &mut CPUState) {
+    let value = cpu_state.get_register(0) as i16;
+    cpu_state.set_register(1, value.signum() as u16);
+}
@@ -14,5 +14,5 @@
 // You should have received a copy of the GNU General Public License
 // along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
 
-mod queue;
-mod task;
+pub mod queue;
+pub mod task;
@@ -15,11 +15,18 @@
 // along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::HashMap;
-use tasks::task::Task;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tasks::task::{Task, Difficulty, make_task_with_rng};
+
+/// Maximum number of tasks that can be issued and not completed at the same time.
+const MAX_ISSUED_TASKS: usize = 16;
 
 /// Defined queue of tasks.
 pub struct TasksQueue {
-    issued_tasks: HashMap<u16, Task>
+    issued_tasks: HashMap<u16, Task>,
+    // Generates the tasks.
+    rng: StdRng,
 }
 
 impl TasksQueue {
@@ -27,10 +34,117 @@ impl TasksQueue {
     pub fn new() -> TasksQueue {
         TasksQueue {
             issued_tasks: HashMap::new(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Creates a queue that issues the same tasks each time it's created
+    /// with the same seed. Useful for tests and tournaments.
+    pub fn new_with_seed(seed: u64) -> TasksQueue {
+        TasksQueue {
+            issued_tasks: HashMap::new(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Issues a new task, and returns its ID.
+    /// Returns None if too many tasks are already issued and not completed.
+    ///
+    /// @difficulty: Difficulty of the task to issue.
+    pub fn get_task(&mut self, difficulty: Difficulty) -> Option<u16> {
+        if self.issued_tasks.len() >= MAX_ISSUED_TASKS {
+            return None;
         }
+
+        let mut task = make_task_with_rng(difficulty, &mut self.rng);
+
+        // IDs are random, so they might collide with one of the issued tasks.
+        while self.issued_tasks.contains_key(&task.get_id()) {
+            task = make_task_with_rng(difficulty, &mut self.rng);
+        }
+
+        let id = task.get_id();
+        self.issued_tasks.insert(id, task);
+
+        return Some(id);
     }
 
-    pub fn get_task() {
+    /// Returns the issued task with the specified ID, if any.
+    pub fn get_issued_task(&self, id: u16) -> Option<&Task> {
+        return self.issued_tasks.get(&id);
+    }
 
+    /// Completes an issued task. The task is removed from the queue, so it
+    /// can only be completed once.
+    /// Returns true if the result was correct, false if it was wrong or the
+    /// task wasn't issued.
+    ///
+    /// @id: ID of the issued task.
+    /// @result: Result that is calculated for the task.
+    pub fn complete_task(&mut self, id: u16, result: u16) -> bool {
+        return match self.issued_tasks.remove(&id) {
+            Some(task) => task.get_expected_result() == result,
+            None => false,
+        };
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issue_and_complete() {
+        let mut queue = TasksQueue::new();
+
+        let id = queue.get_task(Difficulty::Small).unwrap();
+        let expected_result = queue.issued_tasks.get(&id).unwrap().get_expected_result();
+        assert_eq!(queue.issued_tasks.get(&id).unwrap().get_id(), id);
+
+        let other_id = queue.get_task(Difficulty::Small).unwrap();
+        assert_ne!(id, other_id);
+        assert_eq!(queue.issued_tasks.len(), 2);
+
+        assert_eq!(queue.complete_task(id, expected_result), true);
+        assert_eq!(queue.issued_tasks.contains_key(&id), false);
+
+        // Already completed.
+        assert_eq!(queue.complete_task(id, expected_result), false);
+
+        let wrong_result = queue.issued_tasks.get(&other_id).unwrap().get_expected_result()
+            .wrapping_add(1);
+        assert_eq!(queue.complete_task(other_id, wrong_result), false);
+        assert_eq!(queue.issued_tasks.contains_key(&other_id), false);
+
+        // Never issued.
+        assert_eq!(queue.complete_task(0, 0), false);
+    }
+
+    #[test]
+    fn seeded_queues() {
+        let mut first = TasksQueue::new_with_seed(42);
+        let mut second = TasksQueue::new_with_seed(42);
+
+        for _ in 0..3 {
+            let id = first.get_task(Difficulty::Small).unwrap();
+            assert_eq!(second.get_task(Difficulty::Small), Some(id));
+            assert_eq!(first.get_issued_task(id).unwrap().get_code(),
+                       second.get_issued_task(id).unwrap().get_code());
+        }
+    }
+
+    #[test]
+    fn max_issued_tasks() {
+        let mut queue = TasksQueue::new();
+
+        for _ in 0..MAX_ISSUED_TASKS {
+            assert_eq!(queue.get_task(Difficulty::Small).is_some(), true);
+        }
+        assert_eq!(queue.get_task(Difficulty::Small), None);
+
+        // Completing one makes room for another.
+        let id = *queue.issued_tasks.keys().next().unwrap();
+        queue.complete_task(id, 0);
+        assert_eq!(queue.get_task(Difficulty::Small).is_some(), true);
+    }
+}
@@ -14,36 +14,121 @@
 // You should have received a copy of the GNU General Public License
 // along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
 
+#[cfg(test)]
 use rand::prelude::thread_rng;
 use rand::Rng;
+use emulator::Emulator;
+
+/// Tiers of the tasks. Harder tasks have longer programs and more data.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Difficulty {
+    Small,
+    Medium,
+    Large,
+}
 
 pub struct Task {
     id: u16,
+    difficulty: Difficulty,
     code: Vec<u16>,
+    // Input data of the task. They're also at the end of the code.
+    // Bots receive them in registers, so there are at most six of them.
+    data: Vec<u16>,
     expected_result: u16,
 }
 
-/// Creates and returns a random small task (13 instructions).
+impl Task {
+
+    /// Unique ID of the task.
+    pub fn get_id(&self) -> u16 {
+        return self.id;
+    }
+
+    /// How hard the task is.
+    pub fn get_difficulty(&self) -> Difficulty {
+        return self.difficulty;
+    }
+
+    /// Binary code of the task.
+    #[cfg(test)]
+    pub fn get_code(&self) -> &Vec<u16> {
+        return &self.code;
+    }
+
+    /// Input data that the task works on.
+    pub fn get_data(&self) -> &Vec<u16> {
+        return &self.data;
+    }
+
+    /// Result that the task should produce.
+    pub fn get_expected_result(&self) -> u16 {
+        return self.expected_result;
+    }
+
+    /// Runs the code of the task in a fresh emulator, and checks that it
+    /// actually produces the expected result in register two.
+    /// Returns error if it doesn't, which means the task generator is broken.
+    pub fn self_check(&self) -> Result<(), String> {
+        // Task's code ends with a RETURN, so it's called as a subroutine.
+        // It returns to the HALT.
+        let mut code: Vec<u16> = vec![0b0110_000_000000011u16, // Set R0 to 3
+                                      0b0000_000011_000000u16, // Subroutine to R0 (3)
+                                      0b0000000000_000011u16]; // Halt
+        code.extend_from_slice(&self.code);
+
+        let mut emulator = Emulator::new(code.len() as u16);
+        emulator.load(&code, 0)?;
+
+        if let Err(error) = emulator.run(code.len() as u64) {
+            return Err(format!("Task {} failed to run: {}", self.id, error));
+        }
+
+        if !emulator.is_halted() {
+            return Err(format!("Task {} didn't return.", self.id));
+        }
+
+        let result = emulator.get_register(2)?;
+        if result != self.expected_result {
+            return Err(format!("Task {} produced {}, but {} is expected.",
+                               self.id, result, self.expected_result));
+        }
+
+        return Ok(());
+    }
+}
+
+/// Creates and returns a random small task (13 words), using the thread's
+/// random number generator.
+#[cfg(test)]
 pub fn make_small_task() -> Task {
+    return make_small_task_with_rng(&mut thread_rng());
+}
+
+/// Makes a small task. Tasks are reproducible if "rng" is seeded.
+///
+/// @rng: Random number generator that generates data and ID of the task.
+pub fn make_small_task_with_rng<R: Rng>(rng: &mut R) -> Task {
 
     // Three random numbers in DATA.
     // add first one to the second one, three times.
     // subtract third one from the above result, two times.
     // Result is in register two.
     // Return.
+    //
+    // SUBTRACT stores the result in its second operand, so the third number is
+    // loaded into R2 and doubled, and then it's subtracted from the sum.
 
     let mut code: Vec<u16> = Vec::with_capacity(13);
-    let mut rng = thread_rng();
 
     code.push(0b0110_100_000001001u16); // Set R4 to 9
     code.push(0b0001_11_0100_00_0001u16); // Copy memory in 9+PC to R1 (9 is the value of R4)
-    code.push(0b0001_11_0100_00_0010u16); // Copy memory in 9+PC to R2
     code.push(0b0001_11_0100_00_0011u16); // Copy memory in 9+PC to R3
-    code.push(0b0010_00_0001_00_0010u16); // Add R1 to R2
-    code.push(0b0010_00_0001_00_0010u16); // Add R1 to R2
-    code.push(0b0010_00_0001_00_0010u16); // Add R1 to R2
-    code.push(0b0011_00_0010_00_0011u16); // Subtract R2 from R3
-    code.push(0b0011_00_0010_00_0011u16); // Subtract R2 from R3
+    code.push(0b0001_11_0100_00_0010u16); // Copy memory in 9+PC to R2
+    code.push(0b0010_00_0001_00_0011u16); // Add R1 to R3
+    code.push(0b0010_00_0001_00_0011u16); // Add R1 to R3
+    code.push(0b0010_00_0001_00_0011u16); // Add R1 to R3
+    code.push(0b0010_00_0010_00_0010u16); // Add R2 to R2
+    code.push(0b0011_00_0011_00_0010u16); // R2 = R3 - R2
     code.push(0b0000000000_000010u16); // Return
 
     // Three random numbers as data.
@@ -51,43 +136,249 @@ pub fn make_small_task() -> Task {
     let d2: u16 = rng.gen_range(1000, 2000); // It's at least 1000 to prevent underflow of subtraction.
     let d3: u16 = rng.gen_range(1, 500);
 
-    code.push(d1);
-    code.push(d2);
-    code.push(d3);
-
     let expected: u16 = (d1 + d1 + d1 + d2) - (d3 + d3);
 
-    Task {
+    return new_task(rng, Difficulty::Small, code, vec![d1, d2, d3], expected);
+}
+
+/// Creates and returns a random medium task (18 words), using the thread's
+/// random number generator.
+#[cfg(test)]
+pub fn make_medium_task() -> Task {
+    return make_medium_task_with_rng(&mut thread_rng());
+}
+
+/// Makes a medium task. Tasks are reproducible if "rng" is seeded.
+///
+/// @rng: Random number generator that generates data and ID of the task.
+pub fn make_medium_task_with_rng<R: Rng>(rng: &mut R) -> Task {
+
+    // Four random numbers in DATA.
+    // Result is (first * 8) + second + third - (fourth / 2), in register two.
+    // There's no multiply or divide instruction, so shifts are used.
+    //
+    // SHIFT_LEFT, SHIFT_RIGHT and SUBTRACT store the result in their second
+    // operand, so the shift amount is set in R6, and is overwritten by the result.
+
+    let mut code: Vec<u16> = Vec::with_capacity(18);
+
+    code.push(0b0110_100_000001101u16); // Set R4 to 13
+    code.push(0b0001_11_0100_00_0001u16); // Copy memory in 13+PC to R1 (13 is the value of R4)
+    code.push(0b0001_11_0100_00_0010u16); // Copy memory in 13+PC to R2
+    code.push(0b0001_11_0100_00_0011u16); // Copy memory in 13+PC to R3
+    code.push(0b0001_11_0100_00_0101u16); // Copy memory in 13+PC to R5
+    code.push(0b0110_110_000000011u16); // Set R6 to 3
+    code.push(0b1001_00_0001_00_0110u16); // R6 = R1 << 3
+    code.push(0b0010_00_0110_00_0010u16); // Add R6 to R2
+    code.push(0b0010_00_0011_00_0010u16); // Add R3 to R2
+    code.push(0b0110_110_000000001u16); // Set R6 to 1
+    code.push(0b1010_00_0101_00_0110u16); // R6 = R5 >> 1
+    code.push(0b0011_00_0010_00_0110u16); // R6 = R2 - R6
+    code.push(0b0001_00_0110_00_0010u16); // Copy R6 to R2
+    code.push(0b0000000000_000010u16); // Return
+
+    // Four random numbers as data.
+    let d1: u16 = rng.gen_range(1, 500);
+    let d2: u16 = rng.gen_range(1000, 2000); // It's at least 1000 to prevent underflow of subtraction.
+    let d3: u16 = rng.gen_range(1, 1000);
+    let d4: u16 = rng.gen_range(1, 2000);
+
+    let expected: u16 = ((d1 << 3) + d2 + d3) - (d4 >> 1);
+
+    return new_task(rng, Difficulty::Medium, code, vec![d1, d2, d3, d4], expected);
+}
+
+/// Creates and returns a random large task (27 words), using the thread's
+/// random number generator.
+#[cfg(test)]
+pub fn make_large_task() -> Task {
+    return make_large_task_with_rng(&mut thread_rng());
+}
+
+/// Makes a large task. Tasks are reproducible if "rng" is seeded.
+///
+/// @rng: Random number generator that generates data and ID of the task.
+pub fn make_large_task_with_rng<R: Rng>(rng: &mut R) -> Task {
+
+    // Six random numbers in DATA. Result is
+    // (first * 4) + second + third - ((fourth + fifth) / 2) + (sixth * 2)
+    // in register two.
+    //
+    // There aren't enough registers for all the data, so they're copied in
+    // two batches. R4 is set before each batch, so that R4 + PC points to the
+    // data of each COPY.
+
+    let mut code: Vec<u16> = Vec::with_capacity(27);
+
+    code.push(0b0110_100_000010100u16); // Set R4 to 20
+    code.push(0b0001_11_0100_00_0001u16); // Copy memory in 20+PC to R1 (20 is the value of R4)
+    code.push(0b0001_11_0100_00_0010u16); // Copy memory in 20+PC to R2
+    code.push(0b0001_11_0100_00_0011u16); // Copy memory in 20+PC to R3
+    code.push(0b0110_110_000000010u16); // Set R6 to 2
+    code.push(0b1001_00_0001_00_0110u16); // R6 = R1 << 2
+    code.push(0b0010_00_0110_00_0010u16); // Add R6 to R2
+    code.push(0b0010_00_0011_00_0010u16); // Add R3 to R2
+
+    code.push(0b0110_100_000001111u16); // Set R4 to 15
+    code.push(0b0001_11_0100_00_0001u16); // Copy memory in 15+PC to R1
+    code.push(0b0001_11_0100_00_0011u16); // Copy memory in 15+PC to R3
+    code.push(0b0001_11_0100_00_0101u16); // Copy memory in 15+PC to R5
+    code.push(0b0010_00_0001_00_0011u16); // Add R1 to R3
+    code.push(0b0110_110_000000001u16); // Set R6 to 1
+    code.push(0b1010_00_0011_00_0110u16); // R6 = R3 >> 1
+    code.push(0b0011_00_0010_00_0110u16); // R6 = R2 - R6
+    code.push(0b0110_001_000000001u16); // Set R1 to 1
+    code.push(0b1001_00_0101_00_0001u16); // R1 = R5 << 1
+    code.push(0b0010_00_0001_00_0110u16); // Add R1 to R6
+    code.push(0b0001_00_0110_00_0010u16); // Copy R6 to R2
+    code.push(0b0000000000_000010u16); // Return
+
+    // Six random numbers as data.
+    let d1: u16 = rng.gen_range(1, 500);
+    let d2: u16 = rng.gen_range(1000, 2000); // It's at least 1000 to prevent underflow of subtraction.
+    let d3: u16 = rng.gen_range(1, 1000);
+    let d4: u16 = rng.gen_range(1, 1000);
+    let d5: u16 = rng.gen_range(1, 1000);
+    let d6: u16 = rng.gen_range(1, 2000);
+
+    let expected: u16 = ((d1 << 2) + d2 + d3) - ((d4 + d5) >> 1) + (d6 << 1);
+
+    return new_task(rng, Difficulty::Large, code, vec![d1, d2, d3, d4, d5, d6], expected);
+}
+
+/// Makes a task of the specified difficulty.
+///
+/// @rng: Random number generator that generates data and ID of the task.
+pub fn make_task_with_rng<R: Rng>(difficulty: Difficulty, rng: &mut R) -> Task {
+    return match difficulty {
+        Difficulty::Small => make_small_task_with_rng(rng),
+        Difficulty::Medium => make_medium_task_with_rng(rng),
+        Difficulty::Large => make_large_task_with_rng(rng),
+    };
+}
+
+/// Appends the data to the code, and creates the task with a random ID.
+/// In debug builds, the task is self checked, to catch bugs of the generators.
+fn new_task<R: Rng>(rng: &mut R, difficulty: Difficulty, mut code: Vec<u16>, data: Vec<u16>,
+                    expected_result: u16) -> Task {
+    code.extend_from_slice(&data);
+
+    let task = Task {
         id: rng.gen_range(1, 65534),
+        difficulty: difficulty,
         code: code,
-        expected_result: expected,
+        data: data,
+        expected_result: expected_result,
+    };
+
+    if cfg!(debug_assertions) {
+        task.self_check().expect("Generated task is broken. Please report this bug!");
     }
+
+    return task;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use emulator::Emulator;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn seeded_small_tasks() {
+        let first = make_small_task_with_rng(&mut StdRng::seed_from_u64(1075));
+        let second = make_small_task_with_rng(&mut StdRng::seed_from_u64(1075));
+
+        assert_eq!(first.get_id(), second.get_id());
+        assert_eq!(first.get_code(), second.get_code());
+        assert_eq!(first.get_expected_result(), second.get_expected_result());
+    }
+
+    #[test]
+    fn make_task_with_rng_difficulties() {
+        let mut rng = StdRng::seed_from_u64(1085);
+
+        for difficulty in [Difficulty::Small, Difficulty::Medium, Difficulty::Large].iter() {
+            let task = make_task_with_rng(*difficulty, &mut rng);
+            assert_eq!(task.get_difficulty(), *difficulty);
+            assert_eq!(task.self_check(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn self_check() {
+        let mut task = make_small_task();
+        assert_eq!(task.self_check(), Ok(()));
+
+        // Corrupting "Add R2 to R2", so the third number isn't doubled.
+        task.code[7] = 0b0000000000_000000u16; // Nop
+        assert_eq!(task.self_check().is_err(), true);
+    }
+
+    /// Runs the task as a subroutine, and returns its result.
+    fn run_task(task: &Task) -> u16 {
+        let mut code = task.get_code().clone();
+        // Adding a `subroutine' to as the first instruction, because the last one is `return'.
+        code.insert(0, 0b0110_000_000000010u16); // Set R0 to 2
+        code.insert(1, 0b0000_000011_000000u16); // Subroutine to R0 (2)
+
+        let mut emu = Emulator::new(code.len() as u16);
+        emu.load(&code, 0).unwrap();
+
+        // Two instructions above, and then the task up to its `return'.
+        // Tasks have no jumps, so each instruction is executed once.
+        for _ in 0..(2 + task.get_code().len() - task.get_data().len()) {
+            emu.clock().unwrap();
+        }
+        assert_eq!(emu.get_program_counter(), 2);
+
+        return emu.get_register(2).unwrap();
+    }
+
+    #[test]
+    fn medium_tasks() {
+        for _ in 0..5 {
+            let task = make_medium_task();
+
+            assert_eq!(task.get_difficulty(), Difficulty::Medium);
+            assert_eq!(task.get_code().len(), 18);
+            assert_eq!(task.get_data().len(), 4);
+            assert_eq!(run_task(&task), task.get_expected_result());
+        }
+    }
+
+    #[test]
+    fn large_tasks() {
+        for _ in 0..5 {
+            let task = make_large_task();
+
+            assert_eq!(task.get_difficulty(), Difficulty::Large);
+            assert_eq!(task.get_code().len(), 27);
+            assert_eq!(task.get_data().len(), 6);
+            assert_eq!(run_task(&task), task.get_expected_result());
+        }
+    }
 
     /// Runs five random small tasks
     #[test]
     fn five_random_small_tasks() {
-        for i in 0..5 {
+        for _ in 0..5 {
             let task: Task = make_small_task();
 
             let mut emu = Emulator::new(15);
-            let mut code = task.code.clone();
+            let mut code = task.get_code().clone();
             // Adding a `subroutine' to as the first instruction, because the last one is `return'.
-            code.insert(0, 0b0110_000_000000011u16); // Set R0 to 3
-            code.insert(1, 0b0000_000011_000000u16); // Subroutine to R0 (3)
+            code.insert(0, 0b0110_000_000000010u16); // Set R0 to 2
+            code.insert(1, 0b0000_000011_000000u16); // Subroutine to R0 (2)
 
-            emu.load(&code, 0);
+            emu.load(&code, 0).unwrap();
 
-            for i in 0..15 {
+            // Two instructions above, and then the task up to its `return'.
+            for _ in 0..12 {
                 emu.clock().unwrap()
             }
 
-            // TODO: Find a way to validate the result (compare R2 with task.expected_result)
+            assert_eq!(emu.get_register(2).unwrap(), task.get_expected_result());
         }
     }
 }
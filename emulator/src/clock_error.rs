@@ -0,0 +1,80 @@
+// This file is part of Sillyverse.
+// Copyright (C) 2017-2020, Aidin Gharibnavaz <aidin@aidinhut.com>
+//
+// Sillyverse is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Sillyverse is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fmt;
+
+/// Reasons that a clock of the CPU can fail.
+/// This enum is used in the public API of the library.
+#[derive(PartialEq, Clone, Debug)]
+pub enum ClockError {
+    // Value is the instruction.
+    UnknownInstruction(u16),
+    // Value is the program counter.
+    PcOutOfBounds(u16),
+    // Address that an operand points to is beyond the memory.
+    AddressOutOfMemory { address: u16, register: u8 },
+    CallStackOverflow,
+    CallStackUnderflow,
+    DataStackOverflow,
+    DataStackUnderflow,
+    // Sys call handler set the error flag.
+    SyscallFailed,
+    // Sys call is requested, but there's no handler for it. Value is the reason.
+    SyscallUnavailable(&'static str),
+    // Value is the program counter.
+    ExecutingIoRegion(u16),
+    // A previous clock failed, so the hardware can't be clocked anymore.
+    HardwareInErrorState,
+    // Instruction is known, but its operands are not valid. Value is the description.
+    InvalidOperand(String),
+    // Resolving an operand failed. "operand" is its role (source or destination).
+    OperandFault { operand: &'static str, instruction: u16, cause: Box<ClockError> },
+}
+
+impl fmt::Display for ClockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ClockError::UnknownInstruction(instruction) =>
+                write!(f, "Unknown instruction: [{:b}]", instruction),
+            ClockError::PcOutOfBounds(_) =>
+                write!(f, "PC goes beyond the memory!"),
+            ClockError::AddressOutOfMemory { address, register } =>
+                write!(f, "Address is out of memory. Address was [{}] stored in register [{}].",
+                       address, register),
+            ClockError::CallStackOverflow =>
+                write!(f, "Call stack overflow."),
+            ClockError::CallStackUnderflow =>
+                write!(f, "Call stack underflow"),
+            ClockError::DataStackOverflow =>
+                write!(f, "Data stack overflow."),
+            ClockError::DataStackUnderflow =>
+                write!(f, "Data stack underflow."),
+            ClockError::SyscallFailed =>
+                write!(f, "Something went wrong when sys call is called."),
+            ClockError::SyscallUnavailable(reason) =>
+                write!(f, "{}", reason),
+            ClockError::ExecutingIoRegion(_) =>
+                write!(f, "Cannot execute instructions from a memory-mapped I/O region."),
+            ClockError::HardwareInErrorState =>
+                write!(f, "This hardware is in Error state."),
+            ClockError::InvalidOperand(ref description) =>
+                write!(f, "{}", description),
+            ClockError::OperandFault { operand, instruction, ref cause } =>
+                write!(f, "Invalid {} operand of instruction [{:016b}]: {}", operand, instruction, cause),
+        }
+    }
+}
@@ -20,6 +20,12 @@
 pub struct CPUState {
     registers: [u16; 8],
     error_flag: bool,
+    overflow_flag: bool,
+    underflow_flag: bool,
+    program_counter: u16,
+    // True if the sys call set the program counter.
+    program_counter_set: bool,
+    call_stack_depth: u16,
 }
 
 impl CPUState {
@@ -30,6 +36,11 @@ impl CPUState {
         CPUState {
             registers: registers.clone(),
             error_flag: false,
+            overflow_flag: false,
+            underflow_flag: false,
+            program_counter: 0,
+            program_counter_set: false,
+            call_stack_depth: 0,
         }
     }
 
@@ -41,6 +52,56 @@ impl CPUState {
         self.error_flag = value;
     }
 
+    pub fn get_overflow_flag(&self) -> bool {
+        return self.overflow_flag;
+    }
+
+    pub fn set_overflow_flag(&mut self, value: bool) {
+        self.overflow_flag = value;
+    }
+
+    pub fn get_underflow_flag(&self) -> bool {
+        return self.underflow_flag;
+    }
+
+    pub fn set_underflow_flag(&mut self, value: bool) {
+        self.underflow_flag = value;
+    }
+
+    pub fn get_program_counter(&self) -> u16 {
+        return self.program_counter;
+    }
+
+    /// Setting the program counter in a sys call makes the CPU continue from
+    /// the new address, instead of the instruction after the sys call. Even if
+    /// it's set to the address of the sys call itself, i.e. to retry it.
+    pub fn set_program_counter(&mut self, value: u16) {
+        self.program_counter = value;
+        self.program_counter_set = true;
+    }
+
+    /// Returns true if the program counter is set by the sys call.
+    pub fn is_program_counter_set(&self) -> bool {
+        return self.program_counter_set;
+    }
+
+    /// Passes the current program counter to the sys call. Unlike
+    /// set_program_counter, it's not counted as a change.
+    pub(crate) fn init_program_counter(&mut self, value: u16) {
+        self.program_counter = value;
+    }
+
+    /// Number of the return addresses that are in the call stack.
+    pub fn get_call_stack_depth(&self) -> u16 {
+        return self.call_stack_depth;
+    }
+
+    /// Passes the call stack depth to the sys call. Call stack can't be
+    /// changed by a sys call, so only the hardware sets it.
+    pub(crate) fn set_call_stack_depth(&mut self, value: u16) {
+        self.call_stack_depth = value;
+    }
+
     pub fn get_register(&self, index: usize) -> u16 {
         return self.registers[index];
     }
@@ -0,0 +1,249 @@
+// This file is part of Sillyverse.
+// Copyright (C) 2017-2020, Aidin Gharibnavaz <aidin@aidinhut.com>
+//
+// Sillyverse is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Sillyverse is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
+
+/// Translates binary instructions back to their assembly literals.
+
+use hardware::operation_code::{OperationCode, OperationType};
+
+/// Disassembles a single instruction, e.g. `COPY R1 M6`.
+/// Words that are not a valid instruction are returned as `.word 0b...`.
+/// Constant of a two-word instruction (LOAD_IMMEDIATE) is not part of the result.
+pub fn disassemble(instruction: u16) -> String {
+    let operation_code = OperationCode::new(instruction);
+
+    let result = match operation_code.operation_type() {
+        OperationType::NoOperand => disassemble_no_operand(operation_code.code()),
+        OperationType::SingleOperand => disassemble_single_operand(operation_code.code(), instruction),
+        OperationType::DoubleOperand => disassemble_double_operand(operation_code.code(), instruction),
+        OperationType::ExtendedDoubleOperand =>
+            disassemble_extended_double_operand(operation_code.code(), instruction),
+    };
+
+    return match result {
+        Some(literal) => literal,
+        None => format!(".word 0b{:016b}", instruction),
+    };
+}
+
+fn disassemble_no_operand(code: u16) -> Option<String> {
+    let name = match code {
+        0b0000000000_000000u16 => "NOP",
+        0b0000000000_000001u16 => "SYSCALL",
+        0b0000000000_000010u16 => "RETURN",
+        0b0000000000_000011u16 => "HALT",
+        _ => return None,
+    };
+
+    return Some(String::from(name));
+}
+
+fn disassemble_single_operand(code: u16, instruction: u16) -> Option<String> {
+    let name = match code {
+        0b0000_000001_000000u16 => "JUMP",
+        0b0000_000010_000000u16 => "SKIP_IF_ZERO",
+        0b0000_000011_000000u16 => "SUBROUTINE",
+        0b0000_000100_000000u16 => "POPCOUNT",
+        0b0000_000101_000000u16 => "CLZ",
+        0b0000_000110_000000u16 => "CTZ",
+        0b0000_000111_000000u16 => "DEC_SKIP_IF_ZERO",
+        0b0000_001000_000000u16 => "INCREMENT",
+        0b0000_001001_000000u16 => "DECREMENT",
+        0b0000_001010_000000u16 => "PUSH",
+        0b0000_001011_000000u16 => "POP",
+        0b0000_001100_000000u16 => "JUMP_LINK",
+        _ => return None,
+    };
+
+    let address = disassemble_address((instruction & 0b0000_000000_111111u16) as u8)?;
+
+    return Some(format!("{} {}", name, address));
+}
+
+fn disassemble_double_operand(code: u16, instruction: u16) -> Option<String> {
+    let name = match code {
+        0b0001_000000000000u16 => "COPY",
+        0b0010_000000000000u16 => "ADD",
+        0b0011_000000000000u16 => "SUBTRACT",
+        0b0100_000000000000u16 => "SKIP_IF_EQUAL",
+        0b0101_000000000000u16 => "SKIP_IF_GREATER",
+        0b1001_000000000000u16 => "SHIFT_LEFT",
+        0b1010_000000000000u16 => "SHIFT_RIGHT",
+        0b1100_000000000000u16 => "SWAP",
+        0b1101_000000000000u16 => "COMPARE",
+        0b1110_000000000000u16 => "TAKE",
+        0b0110_000000000000u16 => {
+            let register_number = (instruction & 0b0000_111_000000000u16) >> 9;
+            let constant = instruction & 0b0000_000_111111111u16;
+            return Some(format!("SET R{} {}", register_number, constant));
+        },
+        0b1011_000000000000u16 => {
+            // Constant is the next word, which is disassembled separately.
+            let register_number = (instruction & 0b0000_111_000000000u16) >> 9;
+            return Some(format!("LOAD_IMMEDIATE R{}", register_number));
+        },
+        0b0111_000000000000u16 => return disassemble_bit_field("BFX", instruction),
+        0b1000_000000000000u16 => return disassemble_bit_field("BFI", instruction),
+        _ => return None,
+    };
+
+    let first_address = disassemble_address(((instruction & 0b0000_111111_000000u16) >> 6) as u8)?;
+    let second_address = disassemble_address((instruction & 0b0000_000000_111111u16) as u8)?;
+
+    return Some(format!("{} {} {}", name, first_address, second_address));
+}
+
+/// Extended instructions are: 4 bits operation, 2 bits extended operation,
+/// and two 5 bits operands.
+fn disassemble_extended_double_operand(code: u16, instruction: u16) -> Option<String> {
+    let name = match code {
+        0b1111_00_0000000000u16 => "ADD_WRAPPING",
+        0b1111_01_0000000000u16 => "SKIP_IF_GREATER_SIGNED",
+        0b1111_10_0000000000u16 => return disassemble_fill(instruction),
+        _ => return None,
+    };
+
+    let first_address = disassemble_extended_operand(((instruction & 0b0000_00_11111_00000u16) >> 5) as u8);
+    let second_address = disassemble_extended_operand((instruction & 0b0000_00_00000_11111u16) as u8);
+
+    return Some(format!("{} {} {}", name, first_address?, second_address?));
+}
+
+/// FILL operands are three registers: 1111 10 0 sss lll vvv
+fn disassemble_fill(instruction: u16) -> Option<String> {
+    if instruction & 0b0000_00_1_000_000_000u16 != 0 {
+        return None;
+    }
+
+    return Some(format!("FILL R{} R{} R{}",
+                        (instruction & 0b0000_00_0_111_000_000u16) >> 6,
+                        (instruction & 0b0000_00_0_000_111_000u16) >> 3,
+                        instruction & 0b0000_00_0_000_000_111u16));
+}
+
+/// Translates a 5 bit operand (ttrrr) of an extended instruction to its literal.
+fn disassemble_extended_operand(operand: u8) -> Option<String> {
+    return disassemble_address(((operand & 0b11000u8) << 1) | (operand & 0b00111u8));
+}
+
+/// Bit field instructions are: 4 bits operation, 3 bits register, 4 bits
+/// lowest bit of the field, and 5 bits width of the field.
+fn disassemble_bit_field(name: &str, instruction: u16) -> Option<String> {
+    let register_number = (instruction & 0b0000_111_0000_00000u16) >> 9;
+    let lowest_bit = (instruction & 0b0000_000_1111_00000u16) >> 5;
+    let width = instruction & 0b0000_000_0000_11111u16;
+
+    if width == 0 || lowest_bit + width > 16 {
+        return None;
+    }
+
+    return Some(format!("{} R{} {} {}", name, register_number, lowest_bit, width));
+}
+
+/// Translates a 6 bit address to its literal. Returns None if the register
+/// number is invalid.
+fn disassemble_address(address: u8) -> Option<String> {
+    let register_number = address & 0b0000_1111u8;
+
+    if register_number > 7 {
+        return None;
+    }
+
+    let address_type = match address & 0b0011_0000u8 {
+        0b0000_0000u8 => "R",
+        0b0001_0000u8 => "M",
+        0b0010_0000u8 => "RP",
+        _ => "RPM",
+    };
+
+    return Some(format!("{}{}", address_type, register_number));
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate compiler;
+
+    use super::*;
+
+    /// Compiles the line, and checks that disassembling it results the same line.
+    fn round_trip(line: &str) {
+        let assembly = compiler::assemble(line).unwrap();
+        assert_eq!(disassemble(assembly.get_words()[0]), line);
+    }
+
+    #[test]
+    fn no_operand() {
+        round_trip("NOP");
+        round_trip("SYSCALL");
+        round_trip("RETURN");
+        round_trip("HALT");
+    }
+
+    #[test]
+    fn single_operand() {
+        round_trip("JUMP RP3");
+        round_trip("SKIP_IF_ZERO M0");
+        round_trip("SUBROUTINE R7");
+        round_trip("POPCOUNT RPM2");
+        round_trip("CLZ R1");
+        round_trip("CTZ M4");
+        round_trip("DEC_SKIP_IF_ZERO R5");
+        round_trip("INCREMENT M6");
+        round_trip("DECREMENT RPM1");
+        round_trip("PUSH M2");
+        round_trip("POP R4");
+        round_trip("JUMP_LINK RP5");
+    }
+
+    #[test]
+    fn double_operand() {
+        round_trip("COPY R1 M6");
+        round_trip("ADD RPM3 R0");
+        round_trip("SUBTRACT M7 M2");
+        round_trip("SKIP_IF_EQUAL R4 RPM5");
+        round_trip("SKIP_IF_GREATER M1 R1");
+        round_trip("SET R3 511");
+        round_trip("BFX R1 6 4");
+        round_trip("BFI R7 0 16");
+        round_trip("SHIFT_LEFT R1 R2");
+        round_trip("SHIFT_RIGHT M3 RPM4");
+        round_trip("SWAP R1 M2");
+        round_trip("COMPARE RPM6 R3");
+        round_trip("TAKE R1 M5");
+
+        assert_eq!(disassemble(0b1011_011_000000000u16), "LOAD_IMMEDIATE R3");
+    }
+
+    #[test]
+    fn extended_double_operand() {
+        round_trip("ADD_WRAPPING R1 R2");
+        round_trip("ADD_WRAPPING RPM7 M0");
+        round_trip("SKIP_IF_GREATER_SIGNED M3 R4");
+        round_trip("FILL R1 R2 R7");
+    }
+
+    #[test]
+    fn unknown_instruction() {
+        assert_eq!(disassemble(0b1111_111111_111111u16), ".word 0b1111111111111111");
+        assert_eq!(disassemble(0b0000000000_111111u16), ".word 0b0000000000111111");
+        assert_eq!(disassemble(0b0000_111111_000000u16), ".word 0b0000111111000000");
+        // Invalid register number.
+        assert_eq!(disassemble(0b0001_001000_000000u16), ".word 0b0001001000000000");
+        // Unused bit of FILL is set.
+        assert_eq!(disassemble(0b1111_10_1_001_010_011u16), ".word 0b1111101001010011");
+        // Empty bit field.
+        assert_eq!(disassemble(0b0111_001_0110_00000u16), ".word 0b0111001011000000");
+    }
+}
@@ -18,20 +18,231 @@
 /// It emulates CPU and memory of a single machine.
 
 mod operations;
-mod operation_code;
+pub mod operation_code;
 
+use std::cell::RefCell;
 use std::rc::Weak;
+use std::slice::Iter;
 use std::sync::Mutex;
 use std::vec::Vec;
 use CPUState;
+use ClockError;
+use IoHandler;
 use SysCallback;
+use InstructionTrace;
 
 
+/// Defines what happens when program counter goes beyond the end of the memory.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EndOfMemoryBehavior {
+    // Clock returns an error. This is the default.
+    Error,
+    // Hardware halts cleanly. So programs don't need to end with a HALT.
+    Halt,
+}
+
+/// Holds the callback that handles sys calls.
+enum SysCallbackHolder {
+    // Callback is owned by someone else, and shared with the hardware.
+    Shared(Weak<Mutex<dyn SysCallback>>),
+    // Callback is owned by the hardware itself.
+    Owned(Box<dyn SysCallback>),
+}
+
+/// A memory region that its reads and writes are handled by a host callback.
+struct IoMapping {
+    // First address of the region.
+    start: u16,
+    // End of the region (exclusive).
+    end: u16,
+    // Reads are done through an immutable reference of the hardware, so
+    // the handler needs interior mutability.
+    handler: RefCell<Box<dyn IoHandler>>,
+}
+
+/// Full state of the hardware at a point in time. It can be restored later,
+/// e.g. to undo or replay a run. Sys call callback, I/O mappings and the end of
+/// memory behavior are configurations of the hardware, so they're not included.
+#[derive(Clone, PartialEq, Debug)]
+pub struct HardwareSnapshot {
+    memory: Vec<u16>,
+    loaded_extent: usize,
+    program_counter: u16,
+    call_stack: Vec<u16>,
+    data_stack: Vec<u16>,
+    registers: [u16; 8],
+    overflow_flag: bool,
+    underflow_flag: bool,
+    error_flag: bool,
+    halted: bool,
+    clock_count: u64,
+    last_instruction_cost: u16,
+}
+
+impl HardwareSnapshot {
+
+    pub fn get_memory(&self) -> &Vec<u16> {
+        return &self.memory;
+    }
+
+    pub fn get_program_counter(&self) -> u16 {
+        return self.program_counter;
+    }
+
+    pub fn get_call_stack(&self) -> &Vec<u16> {
+        return &self.call_stack;
+    }
+
+    pub fn get_data_stack(&self) -> &Vec<u16> {
+        return &self.data_stack;
+    }
+
+    pub fn get_registers(&self) -> [u16; 8] {
+        return self.registers;
+    }
+
+    pub fn get_overflow_flag(&self) -> bool {
+        return self.overflow_flag;
+    }
+
+    pub fn get_underflow_flag(&self) -> bool {
+        return self.underflow_flag;
+    }
+
+    pub fn get_error_flag(&self) -> bool {
+        return self.error_flag;
+    }
+
+    pub fn is_halted(&self) -> bool {
+        return self.halted;
+    }
+
+    pub fn get_clock_count(&self) -> u64 {
+        return self.clock_count;
+    }
+
+    pub fn get_last_instruction_cost(&self) -> u16 {
+        return self.last_instruction_cost;
+    }
+
+    /// Encodes the snapshot as words, so it can be saved or sent elsewhere.
+    /// Words can be written in the same big-endian format that the loader reads.
+    ///
+    /// Format: memory size, memory, loaded extent, program counter, eight
+    /// registers, flags (bit 0 overflow, bit 1 underflow, bit 2 error, bit 3
+    /// halted), clock count in four words (most significant first), last
+    /// instruction cost, call stack size, call stack, data stack size, data stack.
+    pub fn to_words(&self) -> Vec<u16> {
+        let mut words: Vec<u16> = Vec::with_capacity(self.memory.len() + 20 +
+                                                     self.call_stack.len() + self.data_stack.len());
+
+        words.push(self.memory.len() as u16);
+        words.extend_from_slice(&self.memory);
+        words.push(self.loaded_extent as u16);
+        words.push(self.program_counter);
+        words.extend_from_slice(&self.registers);
+        words.push((self.overflow_flag as u16) |
+                   ((self.underflow_flag as u16) << 1) |
+                   ((self.error_flag as u16) << 2) |
+                   ((self.halted as u16) << 3));
+        for shift in [48, 32, 16, 0].iter() {
+            words.push((self.clock_count >> shift) as u16);
+        }
+        words.push(self.last_instruction_cost);
+        words.push(self.call_stack.len() as u16);
+        words.extend_from_slice(&self.call_stack);
+        words.push(self.data_stack.len() as u16);
+        words.extend_from_slice(&self.data_stack);
+
+        return words;
+    }
+
+    /// Decodes a snapshot that is encoded by to_words.
+    /// Returns error if the words are not a valid snapshot.
+    pub fn from_words(words: &[u16]) -> Result<HardwareSnapshot, &'static str> {
+        let mut words = words.iter();
+
+        let memory_size = next_word(&mut words)? as usize;
+        let memory = next_words(&mut words, memory_size)?;
+        let loaded_extent = next_word(&mut words)? as usize;
+        if loaded_extent > memory_size {
+            return Err("Invalid snapshot: Loaded extent is beyond the memory.");
+        }
+        let program_counter = next_word(&mut words)?;
+
+        let mut registers = [0u16; 8];
+        for register in registers.iter_mut() {
+            *register = next_word(&mut words)?;
+        }
+
+        let flags = next_word(&mut words)?;
+        if flags & !0b1111u16 != 0 {
+            return Err("Invalid snapshot: Unknown flags.");
+        }
+
+        let mut clock_count: u64 = 0;
+        for _ in 0..4 {
+            clock_count = (clock_count << 16) | next_word(&mut words)? as u64;
+        }
+        let last_instruction_cost = next_word(&mut words)?;
+
+        let call_stack_size = next_word(&mut words)? as usize;
+        let call_stack = next_words(&mut words, call_stack_size)?;
+        let data_stack_size = next_word(&mut words)? as usize;
+        let data_stack = next_words(&mut words, data_stack_size)?;
+
+        if words.next().is_some() {
+            return Err("Invalid snapshot: Unexpected words at the end.");
+        }
+
+        return Ok(HardwareSnapshot {
+            memory: memory,
+            loaded_extent: loaded_extent,
+            program_counter: program_counter,
+            call_stack: call_stack,
+            data_stack: data_stack,
+            registers: registers,
+            overflow_flag: flags & 0b0001 != 0,
+            underflow_flag: flags & 0b0010 != 0,
+            error_flag: flags & 0b0100 != 0,
+            halted: flags & 0b1000 != 0,
+            clock_count: clock_count,
+            last_instruction_cost: last_instruction_cost,
+        });
+    }
+}
+
+/// Reads the next word of an encoded snapshot.
+fn next_word(words: &mut Iter<u16>) -> Result<u16, &'static str> {
+    return match words.next() {
+        Some(word) => Ok(*word),
+        None => Err("Invalid snapshot: It's truncated."),
+    };
+}
+
+/// Reads the next "count" words of an encoded snapshot.
+fn next_words(words: &mut Iter<u16>, count: usize) -> Result<Vec<u16>, &'static str> {
+    let mut result: Vec<u16> = Vec::with_capacity(count);
+    for _ in 0..count {
+        result.push(next_word(words)?);
+    }
+    return Ok(result);
+}
+
+// Call stack size of the hardwares that are created with Hardware::new.
+const DEFAULT_CALL_STACK_SIZE: usize = 15;
+
 pub struct Hardware {
     memory: Vec<u16>,
+    // End of the highest loaded data in the memory.
+    loaded_extent: usize,
 
     program_counter: u16,
     call_stack: Vec<u16>,
+    // Maximum depth of the call stack.
+    call_stack_size: usize,
+    // Values that are pushed by the PUSH instruction.
+    data_stack: Vec<u16>,
 
     // There are 8 registers.
     registers: [u16; 8],
@@ -40,7 +251,17 @@ pub struct Hardware {
     underflow_flag: bool,
     error_flag: bool,
 
-    sys_callback: Option<Weak<Mutex<dyn SysCallback>>>,
+    // If true, CPU stopped and clock won't do anything anymore.
+    halted: bool,
+    // Number of the instructions that are executed successfully.
+    clock_count: u64,
+    // Cost of the last clock. See Operations::get_cost
+    last_instruction_cost: u16,
+    end_of_memory_behavior: EndOfMemoryBehavior,
+
+    sys_callback: Option<SysCallbackHolder>,
+    trace: Option<Weak<Mutex<dyn InstructionTrace>>>,
+    io_mappings: Vec<IoMapping>,
 
     operations: operations::Operations,
 }
@@ -50,24 +271,46 @@ impl Hardware {
     ///
     /// @memory_size: Size of the hardware memory. Max is 65536.
     pub fn new(memory_size: u16) -> Hardware {
+        return Hardware::new_with_limits(memory_size, DEFAULT_CALL_STACK_SIZE);
+    }
+
+    /// Creates an instance of the Hardware struct with a custom call stack size.
+    ///
+    /// @memory_size: Size of the hardware memory. Max is 65536.
+    /// @call_stack_size: Maximum number of nested subroutine calls.
+    pub fn new_with_limits(memory_size: u16, call_stack_size: usize) -> Hardware {
 
         Hardware {
             memory: vec![0; memory_size as usize],
+            loaded_extent: 0,
             program_counter: 0,
-            call_stack: Vec::with_capacity(10),
+            call_stack: Vec::with_capacity(call_stack_size),
+            call_stack_size: call_stack_size,
+            data_stack: Vec::new(),
             registers: [0; 8],
             overflow_flag: false,
             underflow_flag: false,
             error_flag: false,
+            halted: false,
+            clock_count: 0,
+            last_instruction_cost: 0,
+            end_of_memory_behavior: EndOfMemoryBehavior::Error,
             sys_callback: None,
+            trace: None,
+            io_mappings: Vec::new(),
             operations: operations::Operations::new(),
         }
     }
 
-    /// Static method.
     /// Returns the maximum size of the call stack.
-    pub fn get_call_stack_size() -> usize {
-        return 15;
+    pub fn get_call_stack_size(&self) -> usize {
+        return self.call_stack_size;
+    }
+
+    /// Static method.
+    /// Returns the maximum size of the data stack.
+    pub fn get_data_stack_size() -> usize {
+        return 32;
     }
 
     /// Loads the specified data into memory.
@@ -80,39 +323,83 @@ impl Hardware {
         // Converting "start" to "usize" for easier usage.
         let start_size: usize = start as usize;
 
+        if self.memory.is_empty() && !data.is_empty() {
+            return Err("Cannot load into zero-size memory.");
+        }
+
         if start_size + data.len() > self.memory.len() {
             return Err("Out of memory: Data won't fit in memory starting from specified address.");
         }
 
-        // TODO: There should be a faster way.
-        for i in 0..data.len() {
-            self.memory[start_size + i] = data[i];
+        self.memory[start_size..start_size + data.len()].copy_from_slice(data);
+
+        if start_size + data.len() > self.loaded_extent {
+            self.loaded_extent = start_size + data.len();
         }
 
         return Ok(());
     }
 
+    /// Returns the memory as it is. Memory-mapped I/O regions are not read
+    /// from their handlers.
+    pub fn get_memory(&self) -> &Vec<u16> {
+        return &self.memory;
+    }
+
+    /// Returns size of the memory.
+    pub fn get_memory_size(&self) -> u16 {
+        return self.memory.len() as u16;
+    }
+
+    /// Returns end of the highest data that is loaded into the memory.
+    /// Memory after this address is never loaded.
+    pub fn get_loaded_extent(&self) -> u16 {
+        return self.loaded_extent as u16;
+    }
+
     /// Executes a clock of CPU.
     /// Returns error only if something really goes wrong
     /// (hardware state is corrupted).
-    pub fn clock(&mut self) -> Result<(), String>{
+    pub fn clock(&mut self) -> Result<(), ClockError> {
 
         if self.error_flag {
-            return Err(String::from("This hardware is in Error state."));
+            return Err(ClockError::HardwareInErrorState);
+        }
+
+        if self.halted {
+            self.last_instruction_cost = operations::IDLE_CLOCK_COST;
+            return Ok(());
         }
 
         // Converting type for easier usage.
         let program_counter = self.program_counter as usize;
 
         if program_counter >= self.memory.len() {
-            return Err(String::from("PC goes beyond the memory!"));
+            if self.end_of_memory_behavior == EndOfMemoryBehavior::Halt {
+                self.halted = true;
+                self.last_instruction_cost = operations::IDLE_CLOCK_COST;
+                return Ok(());
+            }
+            return Err(ClockError::PcOutOfBounds(self.program_counter));
+        }
+
+        if self.find_io_mapping(self.program_counter).is_some() {
+            return Err(ClockError::ExecutingIoRegion(self.program_counter));
         }
 
         // Fetching current instruction.
         let instruction = self.memory[program_counter];
 
-        // Executing instruction. Note the "?" (-:
-        let executer_function = self.operations.get_function(instruction)?;
+        self.call_trace(instruction);
+
+        let executer_function = match self.operations.get_function(instruction) {
+            Ok(function) => function,
+            Err(error) => {
+                // Unknown instruction. This hardware is no longer in a valid state.
+                self.error_flag = true;
+                return Err(error);
+            },
+        };
         let execute_result = executer_function(self, instruction);
 
         if execute_result.is_err() {
@@ -122,9 +409,137 @@ impl Hardware {
         }
 
         // Nothing goes wrong.
+        self.clock_count += 1;
+        self.last_instruction_cost = self.operations.get_cost(instruction);
         return Ok(());
     }
 
+    /// Resets the CPU to its initial state: registers, program counter, flags and
+    /// stacks. Memory is untouched.
+    pub fn reset(&mut self) {
+        self.program_counter = 0;
+        self.call_stack.clear();
+        self.data_stack.clear();
+        self.registers = [0; 8];
+        self.overflow_flag = false;
+        self.underflow_flag = false;
+        self.error_flag = false;
+        self.halted = false;
+        self.clock_count = 0;
+        self.last_instruction_cost = 0;
+    }
+
+    /// Captures the current state of the hardware.
+    pub fn snapshot(&self) -> HardwareSnapshot {
+        HardwareSnapshot {
+            memory: self.memory.clone(),
+            loaded_extent: self.loaded_extent,
+            program_counter: self.program_counter,
+            call_stack: self.call_stack.clone(),
+            data_stack: self.data_stack.clone(),
+            registers: self.registers,
+            overflow_flag: self.overflow_flag,
+            underflow_flag: self.underflow_flag,
+            error_flag: self.error_flag,
+            halted: self.halted,
+            clock_count: self.clock_count,
+            last_instruction_cost: self.last_instruction_cost,
+        }
+    }
+
+    /// Overwrites the state of the hardware with the snapshot, memory included.
+    pub fn restore(&mut self, snapshot: &HardwareSnapshot) {
+        self.memory = snapshot.memory.clone();
+        self.loaded_extent = snapshot.loaded_extent;
+        self.program_counter = snapshot.program_counter;
+        self.call_stack = snapshot.call_stack.clone();
+        self.data_stack = snapshot.data_stack.clone();
+        self.registers = snapshot.registers;
+        self.overflow_flag = snapshot.overflow_flag;
+        self.underflow_flag = snapshot.underflow_flag;
+        self.error_flag = snapshot.error_flag;
+        self.halted = snapshot.halted;
+        self.clock_count = snapshot.clock_count;
+        self.last_instruction_cost = snapshot.last_instruction_cost;
+    }
+
+    /// Runs a tiny known program, and checks its result. Used to validate that
+    /// the hardware works correctly.
+    ///
+    /// CPU will be reset before and after the test, and the memory that the
+    /// program used will be restored. So it should be called on an idle hardware.
+    pub fn self_test(&mut self) -> Result<(), String> {
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b0110_010_000000111u16, // Set R2 to 7
+                        0b0010_000001_000010u16]; // Add R1 to R2
+
+        if self.memory.len() < code.len() {
+            return Err(String::from("Memory is too small to run the self test."));
+        }
+
+        let original_memory: Vec<u16> = self.memory[0..code.len()].to_vec();
+        self.memory[0..code.len()].copy_from_slice(&code);
+        self.reset();
+
+        let mut result: Result<(), String> = Ok(());
+        for _i in 0..code.len() {
+            result = self.clock().map_err(|error| error.to_string());
+            if result.is_err() {
+                break;
+            }
+        }
+
+        if result.is_ok() && (self.registers[2] != 12 || self.program_counter != 3) {
+            result = Err(format!(
+                "Self test failed: Expected 12 in R2 and 3 in PC, found {} and {}.",
+                self.registers[2], self.program_counter));
+        }
+
+        self.memory[0..code.len()].copy_from_slice(&original_memory);
+        self.reset();
+
+        return result;
+    }
+
+    /// Returns a copy of the registers.
+    pub fn get_registers(&self) -> [u16; 8] {
+        return self.registers;
+    }
+
+    pub fn get_program_counter(&self) -> u16 {
+        return self.program_counter;
+    }
+
+    pub fn set_program_counter(&mut self, value: u16) {
+        self.program_counter = value;
+    }
+
+    /// Clears the error flag, so the CPU can be clocked again.
+    pub fn clear_error_flag(&mut self) {
+        self.error_flag = false;
+    }
+
+    /// Whether the CPU is halted.
+    pub fn is_halted(&self) -> bool {
+        return self.halted;
+    }
+
+    /// Number of the clocks that executed an instruction successfully.
+    pub fn get_clock_count(&self) -> u64 {
+        return self.clock_count;
+    }
+
+    /// Cost of the last successful clock. Clocks of a halted CPU cost the same as
+    /// a NOP. Zero if nothing is executed yet.
+    pub fn get_last_instruction_cost(&self) -> u16 {
+        return self.last_instruction_cost;
+    }
+
+    /// Sets what should happen when program counter goes beyond the memory.
+    pub fn set_end_of_memory_behavior(&mut self, behavior: EndOfMemoryBehavior) {
+        self.end_of_memory_behavior = behavior;
+    }
+
     /// Increases the memory by the specified additional bytes.
     ///
     /// Returns error if new size would become more than maxed allowed (65536)
@@ -157,15 +572,141 @@ impl Hardware {
         return Ok(new_size as u16);
     }
 
+    /// Decreases the memory by the specified bytes, from the end of the memory.
+    ///
+    /// Returns error if nothing would be left of the memory, or if the memory
+    /// is smaller than the requested bytes. Memory won't be touched if error return.
+    ///
+    /// If the program counter is left beyond the new end, next clock will fail
+    /// (or halt) as it does for any bad program counter.
+    ///
+    /// Returns new size if everything is Ok.
+    ///
+    /// @remove: Bytes to remove from the memory.
+    pub fn decrease_memory(&mut self, remove: u16) -> Result<u16, &'static str> {
+
+        let current_size = self.memory.len();
+        if remove as usize > current_size {
+            return Err("Cannot remove more bytes than the memory size.");
+        }
+
+        let new_size = current_size - remove as usize;
+        if new_size == 0 {
+            return Err("Memory size cannot become zero.");
+        }
+
+        self.memory.truncate(new_size);
+        if self.loaded_extent > new_size {
+            self.loaded_extent = new_size;
+        }
+
+        return Ok(new_size as u16);
+    }
+
+    /// Maps the specified memory region to a handler. Reads and writes to this
+    /// region will be passed to the handler instead of the memory.
+    ///
+    /// @start: First address of the region.
+    /// @end: End of the region (exclusive).
+    pub fn map_io(&mut self, start: u16, end: u16, handler: Box<dyn IoHandler>)
+        -> Result<(), &'static str> {
+
+        if start >= end {
+            return Err("Start of the I/O region should be less than its end.");
+        }
+
+        if end as usize > self.memory.len() {
+            return Err("I/O region should be inside the memory.");
+        }
+
+        for mapping in self.io_mappings.iter() {
+            if start < mapping.end && mapping.start < end {
+                return Err("I/O region overlaps with another mapped region.");
+            }
+        }
+
+        self.io_mappings.push(IoMapping {
+            start: start,
+            end: end,
+            handler: RefCell::new(handler),
+        });
+
+        return Ok(());
+    }
+
+    /// Returns the I/O mapping that the specified address is in, if any.
+    fn find_io_mapping(&self, address: u16) -> Option<&IoMapping> {
+        return self.io_mappings.iter()
+            .find(|mapping| mapping.start <= address && address < mapping.end);
+    }
+
+    /// Reads value of the specified memory address.
+    /// It's the caller's responsibility to ensure the address is inside the memory.
+    pub fn read_memory(&self, address: u16) -> u16 {
+        if let Some(mapping) = self.find_io_mapping(address) {
+            return mapping.handler.borrow_mut().read(address);
+        }
+
+        return self.memory[address as usize];
+    }
+
+    /// Writes the value into the specified memory address.
+    /// It's the caller's responsibility to ensure the address is inside the memory.
+    pub fn write_memory(&mut self, address: u16, value: u16) {
+        if let Some(mapping) = self.find_io_mapping(address) {
+            mapping.handler.borrow_mut().write(address, value);
+            return;
+        }
+
+        self.memory[address as usize] = value;
+    }
+
+    /// Writes the value into "length" words of the memory, starting from "start".
+    /// Returns error if the range is out of the memory. Memory won't be touched
+    /// if error return.
+    pub fn fill_memory(&mut self, start: u16, length: u16, value: u16) -> Result<(), &'static str> {
+        if start as usize + length as usize > self.memory.len() {
+            return Err("Fill range is out of the memory.");
+        }
+
+        for address in start..start + length {
+            self.write_memory(address, value);
+        }
+
+        return Ok(());
+    }
+
     pub fn register_sys_callback(&mut self, callback: Weak<Mutex<dyn SysCallback>>) {
-        self.sys_callback = Some(callback);
+        self.sys_callback = Some(SysCallbackHolder::Shared(callback));
+    }
+
+    pub fn register_sys_callback_boxed(&mut self, callback: Box<dyn SysCallback>) {
+        self.sys_callback = Some(SysCallbackHolder::Owned(callback));
+    }
+
+    pub fn register_trace(&mut self, trace: Weak<Mutex<dyn InstructionTrace>>) {
+        self.trace = Some(trace);
+    }
+
+    fn call_trace(&self, instruction: u16) {
+        if let Some(ref weak_trace) = self.trace {
+            // A dropped trace is simply ignored. It only observes the execution.
+            if let Some(trace_mutex) = weak_trace.upgrade() {
+                let mut trace = trace_mutex.lock().
+                    expect("Failed to lock the trace callback. Please report this bug!");
+                trace.trace(self.program_counter, instruction);
+            }
+        }
     }
 
     pub fn call_syscall(&mut self, cpu_state: &mut CPUState) -> Result<(), &'static str> {
 
         match self.sys_callback {
             None => return Err("This machine does not support sys calls."),
-            Some(ref weak_callback) => {
+            Some(SysCallbackHolder::Owned(ref mut callback)) => {
+                callback.syscall(cpu_state);
+            },
+            Some(SysCallbackHolder::Shared(ref weak_callback)) => {
                 // Upgrading Weak to Rc to access its value.
                 match weak_callback.upgrade() {
                     // None means this reference is dropped.
@@ -238,6 +779,24 @@ mod tests {
         assert_eq!(hardware.memory[11], 72);
     }
 
+    #[test]
+    fn loaded_extent() {
+        let mut hardware = Hardware::new(12);
+        assert_eq!(hardware.get_memory_size(), 12);
+        assert_eq!(hardware.get_loaded_extent(), 0);
+
+        hardware.load(&vec![1, 2, 3], 4).unwrap();
+        assert_eq!(hardware.get_loaded_extent(), 7);
+
+        // Loading below the current extent doesn't change it.
+        hardware.load(&vec![1, 2], 0).unwrap();
+        assert_eq!(hardware.get_loaded_extent(), 7);
+
+        hardware.increase_memory(4).unwrap();
+        assert_eq!(hardware.get_memory_size(), 16);
+        assert_eq!(hardware.get_loaded_extent(), 7);
+    }
+
     #[test]
     fn load_out_of_memory() {
         let mut hardware = Hardware::new(1024);
@@ -247,6 +806,33 @@ mod tests {
         assert_eq!(load_result.is_err(), true);
     }
 
+    #[test]
+    fn load_zero_size_memory() {
+        let mut hardware = Hardware::new(0);
+
+        assert_eq!(hardware.load(&vec![0u16], 0), Err("Cannot load into zero-size memory."));
+
+        // Nothing to load.
+        hardware.load(&vec![], 0).unwrap();
+    }
+
+    #[test]
+    fn load_large_program() {
+        let mut hardware = Hardware::new(65535);
+
+        let data: Vec<u16> = (0..60000).map(|i| i as u16).collect();
+        hardware.load(&data, 5535).unwrap();
+
+        assert_eq!(hardware.memory[5534], 0);
+        assert_eq!(hardware.memory[5535], 0);
+        assert_eq!(hardware.memory[5536], 1);
+        assert_eq!(hardware.memory[65534], 59999);
+        assert_eq!(hardware.get_loaded_extent(), 65535);
+
+        // One more word won't fit.
+        assert_eq!(hardware.load(&data, 5536).is_err(), true);
+    }
+
     #[test]
     fn increase_memory() {
         let mut hardware = Hardware::new(3000);
@@ -270,6 +856,56 @@ mod tests {
         assert_eq!(result.is_err(), true);
     }
 
+    #[test]
+    fn fill_memory() {
+        let mut hardware = Hardware::new(6);
+
+        hardware.fill_memory(1, 3, 7).unwrap();
+        hardware.compare_memory(&vec![0, 7, 7, 7, 0, 0]);
+
+        // Up to the end of the memory.
+        hardware.fill_memory(4, 2, 9).unwrap();
+        hardware.compare_memory(&vec![0, 7, 7, 7, 9, 9]);
+
+        // Nothing to fill.
+        hardware.fill_memory(6, 0, 1).unwrap();
+
+        assert_eq!(hardware.fill_memory(4, 3, 1).is_err(), true);
+        assert_eq!(hardware.fill_memory(65535, 2, 1).is_err(), true);
+        hardware.compare_memory(&vec![0, 7, 7, 7, 9, 9]);
+    }
+
+    #[test]
+    fn decrease_memory() {
+        let mut hardware = Hardware::new(3000);
+        hardware.load(&vec![1, 2, 3], 2000).unwrap();
+
+        let new_size = hardware.decrease_memory(1000).unwrap();
+        assert_eq!(hardware.memory.len(), 2000);
+        assert_eq!(new_size, 2000);
+        assert_eq!(hardware.get_loaded_extent(), 2000);
+
+        // Shrink to zero error.
+        let result = hardware.decrease_memory(2000);
+        assert_eq!(result.is_err(), true);
+        assert_eq!(hardware.memory.len(), 2000);
+
+        // Removing more than the memory error.
+        let result = hardware.decrease_memory(2001);
+        assert_eq!(result.is_err(), true);
+        assert_eq!(hardware.memory.len(), 2000);
+    }
+
+    #[test]
+    fn decrease_memory_behind_program_counter() {
+        let mut hardware = Hardware::new(10);
+        hardware.program_counter = 8;
+
+        hardware.decrease_memory(5).unwrap();
+
+        assert_eq!(hardware.clock(), Err(ClockError::PcOutOfBounds(8)));
+    }
+
     #[test]
     fn bad_program_counter() {
         let mut hardware = Hardware::new(2000);
@@ -277,42 +913,271 @@ mod tests {
         // Equal to size of memory.
         hardware.program_counter = 2000;
         let clock_result = hardware.clock();
-        assert_eq!(clock_result.is_err(), true);
+        assert_eq!(clock_result, Err(ClockError::PcOutOfBounds(2000)));
 
         // Bigger than memory size.
         hardware.program_counter = 2255;
         let clock_result = hardware.clock();
-        assert_eq!(clock_result.is_err(), true);
+        assert_eq!(clock_result, Err(ClockError::PcOutOfBounds(2255)));
     }
 
     #[test]
-    fn instruction_nop() {
-        let mut hardware = Hardware::new(3);
+    fn self_test() {
+        let mut hardware = Hardware::new(5);
+        hardware.load(&vec![10, 11, 12, 13, 14], 0).unwrap();
 
-        let code = vec![0b0000000000000000u16, 0b0000000000000000u16];
-        hardware.load(&code, 0).unwrap();
+        hardware.self_test().unwrap();
 
+        // Hardware should be left in a clean state.
+        assert_eq!(hardware.registers, [0; 8]);
         assert_eq!(hardware.program_counter, 0);
+        assert_eq!(hardware.error_flag, false);
+        hardware.compare_memory(&vec![10, 11, 12, 13, 14]);
+
+        // Too small memory.
+        let mut hardware = Hardware::new(2);
+        assert_eq!(hardware.self_test().is_err(), true);
+    }
+
+    #[test]
+    fn end_of_memory_error() {
+        let mut hardware = Hardware::new(2);
+
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b0000000000000000u16];
+        hardware.load(&code, 0).unwrap();
 
         hardware.clock().unwrap();
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 2);
 
-        assert_eq!(hardware.program_counter, 1);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers, [0; 8]);
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+        assert_eq!(hardware.is_halted(), false);
+    }
+
+    #[test]
+    fn end_of_memory_halt() {
+        let mut hardware = Hardware::new(2);
+        hardware.set_end_of_memory_behavior(EndOfMemoryBehavior::Halt);
+
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b0000000000000000u16];
+        hardware.load(&code, 0).unwrap();
 
+        hardware.clock().unwrap();
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 2);
+        assert_eq!(hardware.is_halted(), false);
 
         hardware.clock().unwrap();
+        assert_eq!(hardware.is_halted(), true);
 
+        // Clocking a halted hardware changes nothing.
+        hardware.clock().unwrap();
+        assert_eq!(hardware.is_halted(), true);
         assert_eq!(hardware.program_counter, 2);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers, [0; 8]);
+        assert_eq!(hardware.registers[1], 5);
     }
 
     #[test]
-    fn instruction_jump() {
-        // Testing all four types of addresses.
-
-        let mut hardware = Hardware::new(11);
+    fn snapshot_restore() {
+        let mut hardware = Hardware::new(6);
+
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b0000_000011_000010u16, // Subroutine to R2 (3)
+                        0b0000000000000000u16,
+                        0b0001_000001_010011u16, // Copy R1 => Register 3 -> Memory 5
+                        0b0010_000100_000100u16, // Register 4 + Register 4 (saturates)
+                        0b0000000000000000u16];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[2] = 3;
+        hardware.registers[3] = 5;
+        hardware.registers[4] = 60000;
+        hardware.clock().unwrap();
+
+        let snapshot = hardware.snapshot();
+        assert_eq!(snapshot.get_program_counter(), 1);
+        assert_eq!(snapshot.get_registers(), [0, 5, 3, 5, 60000, 0, 0, 0]);
+        assert_eq!(snapshot.get_clock_count(), 1);
+        assert_eq!(snapshot.get_last_instruction_cost(), 1);
+
+        hardware.clock().unwrap();
+        hardware.clock().unwrap();
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[5], 5);
+        assert_eq!(hardware.call_stack, vec![2]);
+        assert_eq!(hardware.overflow_flag, true);
+        // Runs to a bad instruction.
+        assert_eq!(hardware.clock().is_err(), true);
+        assert_eq!(hardware.clock_count, 4);
+        assert_eq!(hardware.last_instruction_cost, 1);
+
+        hardware.restore(&snapshot);
+
+        assert_eq!(hardware.snapshot(), snapshot);
+        assert_eq!(hardware.program_counter, 1);
+        assert_eq!(hardware.registers, [0, 5, 3, 5, 60000, 0, 0, 0]);
+        assert_eq!(hardware.call_stack, Vec::<u16>::new());
+        assert_eq!(hardware.overflow_flag, false);
+        assert_eq!(hardware.error_flag, false);
+        assert_eq!(hardware.clock_count, 1);
+        assert_eq!(hardware.last_instruction_cost, 1);
+        hardware.compare_memory(&code);
+
+        // It runs again from the snapshot.
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 3);
+        assert_eq!(hardware.clock_count, 2);
+        assert_eq!(hardware.last_instruction_cost, 2);
+    }
+
+    #[test]
+    fn snapshot_words() {
+        let mut hardware = Hardware::new(6);
+
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b0000_001010_000001u16, // Push R1
+                        0b0000_000011_000010u16, // Subroutine to R2 (4)
+                        0b0000000000000000u16,
+                        0b0010_000100_000100u16]; // Register 4 + Register 4 (saturates)
+        hardware.load(&code, 0).unwrap();
+        hardware.registers[2] = 4;
+        hardware.registers[4] = 60000;
+
+        for _ in 0..4 {
+            hardware.clock().unwrap();
+        }
+        hardware.clock_count += 0x1_0000_0000;
+
+        let snapshot = hardware.snapshot();
+        assert_eq!(snapshot.get_call_stack(), &vec![3]);
+        assert_eq!(snapshot.get_data_stack(), &vec![5]);
+        assert_eq!(snapshot.get_overflow_flag(), true);
+
+        let words = snapshot.to_words();
+        assert_eq!(HardwareSnapshot::from_words(&words), Ok(snapshot.clone()));
+
+        // Restored snapshot continues the same.
+        let mut restored = Hardware::new(1);
+        restored.restore(&HardwareSnapshot::from_words(&words).unwrap());
+        assert_eq!(restored.snapshot(), snapshot);
+        assert_eq!(restored.clock_count, 0x1_0000_0004);
+
+        // Errors
+        assert_eq!(HardwareSnapshot::from_words(&words[..words.len() - 1]),
+                   Err("Invalid snapshot: It's truncated."));
+        assert_eq!(HardwareSnapshot::from_words(&[]), Err("Invalid snapshot: It's truncated."));
+
+        let mut extra_words = words.clone();
+        extra_words.push(0);
+        assert_eq!(HardwareSnapshot::from_words(&extra_words),
+                   Err("Invalid snapshot: Unexpected words at the end."));
+    }
+
+    /// Clocks a program of NOPs that fills the whole memory.
+    #[test]
+    fn clock_long_nop_program() {
+        let size: u16 = 65535;
+        let mut hardware = Hardware::new(size);
+        hardware.set_end_of_memory_behavior(EndOfMemoryBehavior::Halt);
+
+        let code = vec![0u16; size as usize];
+        hardware.load(&code, 0).unwrap();
+
+        for _ in 0..size {
+            hardware.clock().unwrap();
+        }
+
+        assert_eq!(hardware.program_counter, size);
+        assert_eq!(hardware.clock_count, size as u64);
+        assert_eq!(hardware.halted, false);
+
+        // PC is beyond the memory now.
+        hardware.clock().unwrap();
+        assert_eq!(hardware.halted, true);
+        assert_eq!(hardware.clock_count, size as u64);
+    }
+
+    #[test]
+    fn instruction_nop() {
+        let mut hardware = Hardware::new(3);
+
+        let code = vec![0b0000000000000000u16, 0b0000000000000000u16];
+        hardware.load(&code, 0).unwrap();
+
+        assert_eq!(hardware.program_counter, 0);
+
+        hardware.clock().unwrap();
+
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers, [0; 8]);
+
+
+        hardware.clock().unwrap();
+
+        assert_eq!(hardware.program_counter, 2);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers, [0; 8]);
+    }
+
+    #[test]
+    fn instruction_halt() {
+        let mut hardware = Hardware::new(3);
+
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b0000000000_000011u16, // Halt
+                        0b0110_001_000000111u16]; // Set R1 to 7
+        hardware.load(&code, 0).unwrap();
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.is_halted(), false);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.is_halted(), true);
+        assert_eq!(hardware.program_counter, 1);
+
+        // Clocking a halted CPU does nothing.
+        for _ in 0..5 {
+            hardware.clock().unwrap();
+            assert_eq!(hardware.program_counter, 1);
+            assert_eq!(hardware.registers[1], 5);
+        }
+    }
+
+    #[test]
+    fn instruction_jump_link() {
+        let mut hardware = Hardware::new(8);
+
+        let code = vec![0b0000_000000_000000u16,
+                        0b0000_001100_000010u16, // Register 2 is address 5
+                        0b0000_000000_000000u16,
+                        0b0000_000000_000000u16,
+                        0b0000_000000_000000u16,
+                        0b0000_001100_000111u16, // Register 7 (the link register) is address 2
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[2] = 5;
+        hardware.clock().unwrap();
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 5);
+        assert_eq!(hardware.registers[7], 2);
+        assert_eq!(hardware.call_stack, Vec::<u16>::new());
+
+        // Returning through the link register.
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 2);
+        assert_eq!(hardware.registers[7], 6);
+    }
+
+    #[test]
+    fn instruction_jump() {
+        // Testing all four types of addresses.
+
+        let mut hardware = Hardware::new(11);
 
         let code = vec![0b0000_000001_000010u16, // register 2 is address 3
                         0b0000000000000000u16,
@@ -397,6 +1262,54 @@ mod tests {
         assert_eq!(hardware.registers[6], 0b0000000_100011111u16);
     }
 
+    #[test]
+    fn instruction_subroutine_overflow() {
+        let mut hardware = Hardware::new(1);
+
+        // Calls itself until the call stack is full.
+        hardware.load(&vec![0b0000_000011_000000u16], 0).unwrap();
+
+        for i in 0..hardware.get_call_stack_size() {
+            hardware.clock().unwrap();
+            assert_eq!(hardware.call_stack.len(), i + 1);
+            assert_eq!(hardware.call_stack[i], 1);
+        }
+        assert_eq!(hardware.overflow_flag, false);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result, Err(ClockError::CallStackOverflow));
+        assert_eq!(hardware.overflow_flag, true);
+    }
+
+    #[test]
+    fn instruction_subroutine_custom_call_stack_size() {
+        let mut hardware = Hardware::new_with_limits(1, 2);
+        assert_eq!(hardware.get_call_stack_size(), 2);
+
+        // Calls itself until the call stack is full.
+        hardware.load(&vec![0b0000_000011_000000u16], 0).unwrap();
+
+        hardware.clock().unwrap();
+        hardware.clock().unwrap();
+        assert_eq!(hardware.call_stack, vec![1, 1]);
+
+        assert_eq!(hardware.clock(), Err(ClockError::CallStackOverflow));
+        assert_eq!(hardware.overflow_flag, true);
+    }
+
+    #[test]
+    fn instruction_return_underflow() {
+        let mut hardware = Hardware::new(1);
+
+        // Return without a call.
+        hardware.load(&vec![0b0000000000_000010u16], 0).unwrap();
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result, Err(ClockError::CallStackUnderflow));
+        assert_eq!(hardware.underflow_flag, true);
+        assert_eq!(hardware.program_counter, 0);
+    }
+
     #[test]
     fn instruction_skip_if_zero() {
         let mut hardware = Hardware::new(11);
@@ -432,177 +1345,388 @@ mod tests {
     }
 
     #[test]
-    fn instruction_copy() {
-        let mut hardware = Hardware::new(19);
+    fn instruction_popcount() {
+        let mut hardware = Hardware::new(6);
 
-        // -> means "points", => means "copies"
-        let code = vec![0b0001_000010_000111u16, // register two => seven
-                        0b0001_010011_000110u16, // Register 3 -> memory 9 => register six
-                        0b0001_010011_010100u16, // Register 3 -> memory 7 => Register 4 -> memory 12
-                        0b0001_110000_000001u16, // Register 0 + PC (3) -> Memory 15 => Register 1
-                        0b0001_010101_110110u16, // Register 5 -> Memory 18 => Register 6 + PC (4) -> Memory 17
-                        0b0001_100000_000000u16, // Unsupported address type.
-                        0b0000_000000_000000u16,
-                        // Data
-                        1200u16,
-                        0u16,
-                        2400u16,
-                        13u16, // Used as address
-                        0u16,
-                        1u16,
-                        12564u16,
-                        0u16,
-                        129u16,
-                        0u16,
-                        8u16,
-                        0u16,];
+        let code = vec![0b0000_000100_000001u16, // Register 1
+                        0b0000_000100_000010u16, // Register 2
+                        0b0000_000100_010011u16, // Register 3 -> Memory 5
+                        0b0000_000100_100001u16, // Unsupported address type
+                        0b0000000000000000u16,
+                        0b1010000011110001u16, // Mixed value
+                        ];
         hardware.load(&code, 0).unwrap();
 
-        // Copying two registers
-        hardware.registers[2] = 256;
+        hardware.registers[1] = 0;
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[7], hardware.registers[2]);
+        assert_eq!(hardware.registers[1], 0);
         assert_eq!(hardware.program_counter, 1);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[2], 256);
 
-        hardware.registers[3] = 9;
+        hardware.registers[2] = 0xFFFF;
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[6], hardware.memory[9]);
+        assert_eq!(hardware.registers[2], 16);
         assert_eq!(hardware.program_counter, 2);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[3], 9);
-        assert_eq!(hardware.memory[9], 2400);
 
-        hardware.registers[3] = 7;
-        hardware.registers[4] = 12;
+        hardware.registers[3] = 5;
         hardware.clock().unwrap();
-        assert_eq!(hardware.memory[7], hardware.memory[12]);
+        assert_eq!(hardware.memory[5], 7);
         assert_eq!(hardware.program_counter, 3);
         // Nothing else should be changed.
-        assert_eq!(hardware.memory[7], 1200);
-        assert_eq!(hardware.registers[3], 7);
-        assert_eq!(hardware.registers[4], 12);
-
-        hardware.registers[0] = 12;
-        hardware.clock().unwrap();
-        assert_eq!(hardware.memory[15], hardware.registers[1]);
-        assert_eq!(hardware.program_counter, 4);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[0], 12);
-        assert_eq!(hardware.memory[15], 129);
-
-        hardware.registers[5] = 18;
-        hardware.registers[6] = 13;
-        hardware.clock().unwrap();
-        assert_eq!(hardware.memory[18], hardware.memory[17]);
-        assert_eq!(hardware.program_counter, 5);
-        // Nothing else should be changed.
-        assert_eq!(hardware.memory[18], 0);
-        assert_eq!(hardware.registers[5], 18);
-        assert_eq!(hardware.registers[6], 13);
+        assert_eq!(hardware.registers[3], 5);
 
-        // Error: Register plus PC is not supported.
         let clock_result = hardware.clock();
         assert_eq!(clock_result.is_err(), true);
     }
 
     #[test]
-    fn instruction_add() {
-        let mut hardware = Hardware::new(19);
-
-        // -> means "points"
-        let code = vec![0b0010_000010_000111u16, // register two + register seven
-                        0b0010_010011_000110u16, // Register 3 -> memory 9 + register six
-                        0b0010_010011_010100u16, // Register 3 -> memory 7 + Register 4 -> memory 12
-                        0b0010_110000_000001u16, // [Register 0 + PC (3)] -> Memory 15 + Register 1
-                        0b0010_010101_110110u16, // Register 5 -> Memory 17 + [Register 6 + PC (4)] -> Memory 18
-                        0b0010_000100_000100u16, // Register 4 + Register 4
-                        0b0010_100000_000000u16, // Unsupported address type.
-                        // Data
-                        1200u16,
-                        0u16,
-                        2400u16,
-                        13u16, // Used as address
-                        0u16,
-                        1u16,
-                        12564u16,
-                        0u16,
-                        129u16,
-                        0u16,
-                        8u16,
-                        0u16,];
+    fn instruction_count_leading_zeros() {
+        let mut hardware = Hardware::new(5);
+
+        let code = vec![0b0000_000101_000001u16, // Register 1
+                        0b0000_000101_000010u16, // Register 2
+                        0b0000_000101_010011u16, // Register 3 -> Memory 4
+                        0b0000_000101_100001u16, // Unsupported address type
+                        0b0000000000100111u16, // Odd value
+                        ];
         hardware.load(&code, 0).unwrap();
 
-        hardware.registers[2] = 256;
-        hardware.registers[7] = 100;
+        hardware.registers[1] = 0;
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[7], 356);
+        assert_eq!(hardware.registers[1], 16);
         assert_eq!(hardware.program_counter, 1);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[2], 256);
 
-        hardware.registers[3] = 9;
-        hardware.registers[6] = 8000;
+        hardware.registers[2] = 0b0000_0001_0000_0000u16;
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[6], 10400);
+        assert_eq!(hardware.registers[2], 7);
         assert_eq!(hardware.program_counter, 2);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[3], 9);
-        assert_eq!(hardware.memory[9], 2400);
 
-        hardware.registers[3] = 7;
-        hardware.registers[4] = 12;
+        hardware.registers[3] = 4;
         hardware.clock().unwrap();
-        assert_eq!(hardware.memory[12], 1201);
+        assert_eq!(hardware.memory[4], 10);
         assert_eq!(hardware.program_counter, 3);
-        // Nothing else should be changed.
-        assert_eq!(hardware.memory[7], 1200);
-        assert_eq!(hardware.registers[3], 7);
-        assert_eq!(hardware.registers[4], 12);
-
-        hardware.registers[0] = 12;
-        hardware.registers[1] = 200;
-        hardware.clock().unwrap();
-        assert_eq!(hardware.registers[1], 329);
-        assert_eq!(hardware.program_counter, 4);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[0], 12);
-        assert_eq!(hardware.memory[15], 129);
-
-        hardware.registers[5] = 17;
-        hardware.registers[6] = 14;
-        hardware.clock().unwrap();
-        assert_eq!(hardware.memory[18], 8);
-        assert_eq!(hardware.program_counter, 5);
-        // Nothing else should be changed.
-        assert_eq!(hardware.memory[17], 8);
-        assert_eq!(hardware.registers[5], 17);
-        assert_eq!(hardware.registers[6], 14);
-
-        // Saturating add
-        hardware.registers[4] = 60000;
-        hardware.clock().unwrap();
-        assert_eq!(hardware.registers[4], u16::MAX);
-        assert_eq!(hardware.program_counter, 6);
 
-        // Error: Register plus PC is not supported.
         let clock_result = hardware.clock();
         assert_eq!(clock_result.is_err(), true);
     }
 
     #[test]
-    fn instruction_subtract() {
-        let mut hardware = Hardware::new(19);
+    fn instruction_count_trailing_zeros() {
+        let mut hardware = Hardware::new(5);
+
+        let code = vec![0b0000_000110_000001u16, // Register 1
+                        0b0000_000110_000010u16, // Register 2
+                        0b0000_000110_010011u16, // Register 3 -> Memory 4
+                        0b0000_000110_100001u16, // Unsupported address type
+                        0b0000000000100111u16, // Odd value
+                        ];
+        hardware.load(&code, 0).unwrap();
 
-        // -> means "points"
-        let code = vec![0b0011_000010_000111u16, // register two - register seven
-                        0b0011_010011_000110u16, // Register 3 -> memory 9 - register six
-                        0b0011_010011_010100u16, // Register 3 -> memory 7 - Register 4 -> memory 12
-                        0b0011_110000_000001u16, // Register 0 + PC (3) -> Memory 15 - Register 1
-                        0b0011_010101_110110u16, // Register 5 -> Memory 17 - Register 6 + PC (4) -> Memory 18
-                        0b0011_000101_000100u16, // Register 5 -> Memory 8 - Register 4
-                        0b0011_100000_000000u16, // Unsupported address type.
+        hardware.registers[1] = 0;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 16);
+        assert_eq!(hardware.program_counter, 1);
+
+        hardware.registers[2] = 0b0000_0001_0000_0000u16;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], 8);
+        assert_eq!(hardware.program_counter, 2);
+
+        hardware.registers[3] = 4;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[4], 0);
+        assert_eq!(hardware.program_counter, 3);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_dec_skip_if_zero() {
+        let mut hardware = Hardware::new(7);
+
+        // A loop that counts register 1 down to zero.
+        let code = vec![0b0000_000111_000001u16, // Decrement register 1, skip if zero
+                        0b0000_000001_000010u16, // Jump to register 2 (0)
+                        0b0000_000111_010011u16, // Register 3 -> Memory 6
+                        0b0000000000000000u16,
+                        0b0000_000111_100001u16, // Unsupported address type
+                        0b0000000000000000u16,
+                        0b0000000000000000u16, // Already zero
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 3;
+        hardware.registers[2] = 0;
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 2);
+        assert_eq!(hardware.program_counter, 1);
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 0);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 1);
+        assert_eq!(hardware.program_counter, 1);
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 0);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 0);
+        assert_eq!(hardware.program_counter, 2);
+
+        // Already zero: stays zero, and skips.
+        hardware.registers[3] = 6;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[6], 0);
+        assert_eq!(hardware.program_counter, 4);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_increment() {
+        let mut hardware = Hardware::new(6);
+
+        let code = vec![0b0000_001000_000001u16, // Register 1
+                        0b0000_001000_010010u16, // Register 2 -> Memory 5
+                        0b0000_001000_010010u16, // Register 2 -> Memory 5 (saturates)
+                        0b0000_001000_100001u16, // Unsupported address type
+                        0u16,
+                        65534u16,
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 0;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 1);
+        assert_eq!(hardware.program_counter, 1);
+
+        hardware.registers[2] = 5;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[5], u16::MAX);
+        assert_eq!(hardware.program_counter, 2);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[5], u16::MAX);
+        assert_eq!(hardware.program_counter, 3);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[2], 5);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_push_pop() {
+        let mut hardware = Hardware::new(8);
+
+        let code = vec![0b0000_001010_000001u16, // Push Register 1
+                        0b0000_001010_010010u16, // Push Register 2 -> Memory 7
+                        0b0000_001011_000011u16, // Pop to Register 3
+                        0b0000_001011_010100u16, // Pop to Register 4 -> Memory 6
+                        0b0000_001010_100001u16, // Unsupported address type
+                        0b0000_001011_100001u16, // Unsupported address type
+                        0u16,
+                        900u16,
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 12;
+        hardware.registers[2] = 7;
+        hardware.clock().unwrap();
+        hardware.clock().unwrap();
+        assert_eq!(hardware.data_stack, vec![12, 900]);
+        assert_eq!(hardware.program_counter, 2);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[3], 900);
+
+        hardware.registers[4] = 6;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[6], 12);
+        assert_eq!(hardware.data_stack, Vec::<u16>::new());
+        assert_eq!(hardware.program_counter, 4);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+
+        hardware.error_flag = false;
+        hardware.program_counter = 5;
+        hardware.data_stack.push(1);
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+        // Value shouldn't be lost.
+        assert_eq!(hardware.data_stack, vec![1]);
+    }
+
+    #[test]
+    fn instruction_push_overflow() {
+        let mut hardware = Hardware::new(1);
+
+        hardware.load(&vec![0b0000_001010_000000u16], 0).unwrap();
+
+        for i in 0..Hardware::get_data_stack_size() {
+            hardware.program_counter = 0;
+            hardware.registers[0] = i as u16;
+            hardware.clock().unwrap();
+        }
+        assert_eq!(hardware.data_stack.len(), Hardware::get_data_stack_size());
+        assert_eq!(hardware.overflow_flag, false);
+
+        hardware.program_counter = 0;
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result, Err(ClockError::DataStackOverflow));
+        assert_eq!(hardware.overflow_flag, true);
+        assert_eq!(hardware.program_counter, 0);
+    }
+
+    #[test]
+    fn instruction_pop_underflow() {
+        let mut hardware = Hardware::new(1);
+
+        hardware.load(&vec![0b0000_001011_000000u16], 0).unwrap();
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result, Err(ClockError::DataStackUnderflow));
+        assert_eq!(hardware.underflow_flag, true);
+        assert_eq!(hardware.program_counter, 0);
+    }
+
+    #[test]
+    fn instruction_decrement() {
+        let mut hardware = Hardware::new(6);
+
+        let code = vec![0b0000_001001_000001u16, // Register 1
+                        0b0000_001001_010010u16, // Register 2 -> Memory 5
+                        0b0000_001001_010010u16, // Register 2 -> Memory 5 (saturates)
+                        0b0000_001001_100001u16, // Unsupported address type
+                        0u16,
+                        1u16,
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 1;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 0);
+        assert_eq!(hardware.program_counter, 1);
+
+        hardware.registers[2] = 5;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[5], 0);
+        assert_eq!(hardware.program_counter, 2);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[5], 0);
+        assert_eq!(hardware.program_counter, 3);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_copy_source_out_of_memory() {
+        let mut hardware = Hardware::new(4);
+        hardware.load(&vec![0b0001_010011_000110u16], 0).unwrap(); // Register 3 -> memory 900 => register six
+        hardware.registers[3] = 900;
+
+        let error = hardware.clock().unwrap_err();
+        assert_eq!(error, ClockError::OperandFault {
+            operand: "source",
+            instruction: 0b0001_010011_000110u16,
+            cause: Box::new(ClockError::AddressOutOfMemory { address: 900, register: 3 }),
+        });
+        assert_eq!(format!("{}", error).contains("source"), true);
+    }
+
+    #[test]
+    fn instruction_copy() {
+        let mut hardware = Hardware::new(19);
+
+        // -> means "points", => means "copies"
+        let code = vec![0b0001_000010_000111u16, // register two => seven
+                        0b0001_010011_000110u16, // Register 3 -> memory 9 => register six
+                        0b0001_010011_010100u16, // Register 3 -> memory 7 => Register 4 -> memory 12
+                        0b0001_110000_000001u16, // Register 0 + PC (3) -> Memory 15 => Register 1
+                        0b0001_010101_110110u16, // Register 5 -> Memory 18 => Register 6 + PC (4) -> Memory 17
+                        0b0001_100000_000000u16, // Unsupported address type.
+                        0b0000_000000_000000u16,
+                        // Data
+                        1200u16,
+                        0u16,
+                        2400u16,
+                        13u16, // Used as address
+                        0u16,
+                        1u16,
+                        12564u16,
+                        0u16,
+                        129u16,
+                        0u16,
+                        8u16,
+                        0u16,];
+        hardware.load(&code, 0).unwrap();
+
+        // Copying two registers
+        hardware.registers[2] = 256;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[7], hardware.registers[2]);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[2], 256);
+
+        hardware.registers[3] = 9;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[6], hardware.memory[9]);
+        assert_eq!(hardware.program_counter, 2);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[3], 9);
+        assert_eq!(hardware.memory[9], 2400);
+
+        hardware.registers[3] = 7;
+        hardware.registers[4] = 12;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[7], hardware.memory[12]);
+        assert_eq!(hardware.program_counter, 3);
+        // Nothing else should be changed.
+        assert_eq!(hardware.memory[7], 1200);
+        assert_eq!(hardware.registers[3], 7);
+        assert_eq!(hardware.registers[4], 12);
+
+        hardware.registers[0] = 12;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[15], hardware.registers[1]);
+        assert_eq!(hardware.program_counter, 4);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[0], 12);
+        assert_eq!(hardware.memory[15], 129);
+
+        hardware.registers[5] = 18;
+        hardware.registers[6] = 13;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[18], hardware.memory[17]);
+        assert_eq!(hardware.program_counter, 5);
+        // Nothing else should be changed.
+        assert_eq!(hardware.memory[18], 0);
+        assert_eq!(hardware.registers[5], 18);
+        assert_eq!(hardware.registers[6], 13);
+
+        // Error: Register plus PC is not supported.
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_add() {
+        let mut hardware = Hardware::new(19);
+
+        // -> means "points"
+        let code = vec![0b0010_000010_000111u16, // register two + register seven
+                        0b0010_010011_000110u16, // Register 3 -> memory 9 + register six
+                        0b0010_010011_010100u16, // Register 3 -> memory 7 + Register 4 -> memory 12
+                        0b0010_110000_000001u16, // [Register 0 + PC (3)] -> Memory 15 + Register 1
+                        0b0010_010101_110110u16, // Register 5 -> Memory 17 + [Register 6 + PC (4)] -> Memory 18
+                        0b0010_000100_000100u16, // Register 4 + Register 4
+                        0b0010_100000_000000u16, // Unsupported address type.
                         // Data
                         1200u16,
                         0u16,
@@ -614,187 +1738,754 @@ mod tests {
                         0u16,
                         129u16,
                         0u16,
-                        8u16,
-                        0u16,];
+                        8u16,
+                        0u16,];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[2] = 256;
+        hardware.registers[7] = 100;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[7], 356);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[2], 256);
+
+        hardware.registers[3] = 9;
+        hardware.registers[6] = 8000;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[6], 10400);
+        assert_eq!(hardware.program_counter, 2);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[3], 9);
+        assert_eq!(hardware.memory[9], 2400);
+
+        hardware.registers[3] = 7;
+        hardware.registers[4] = 12;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[12], 1201);
+        assert_eq!(hardware.program_counter, 3);
+        // Nothing else should be changed.
+        assert_eq!(hardware.memory[7], 1200);
+        assert_eq!(hardware.registers[3], 7);
+        assert_eq!(hardware.registers[4], 12);
+
+        hardware.registers[0] = 12;
+        hardware.registers[1] = 200;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 329);
+        assert_eq!(hardware.program_counter, 4);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[0], 12);
+        assert_eq!(hardware.memory[15], 129);
+
+        hardware.registers[5] = 17;
+        hardware.registers[6] = 14;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[18], 8);
+        assert_eq!(hardware.program_counter, 5);
+        // Nothing else should be changed.
+        assert_eq!(hardware.memory[17], 8);
+        assert_eq!(hardware.registers[5], 17);
+        assert_eq!(hardware.registers[6], 14);
+
+        assert_eq!(hardware.overflow_flag, false);
+
+        // Saturating add
+        hardware.registers[4] = 60000;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[4], u16::MAX);
+        assert_eq!(hardware.program_counter, 6);
+        assert_eq!(hardware.overflow_flag, true);
+
+        // Error: Register plus PC is not supported.
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_add_overflow_flag() {
+        let mut hardware = Hardware::new(2);
+
+        let code = vec![0b0010_000001_000010u16, // Register 1 + Register 2
+                        0b0010_000001_000011u16]; // Register 1 + Register 3
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 60000;
+        hardware.registers[2] = 60000;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], u16::MAX);
+        assert_eq!(hardware.overflow_flag, true);
+
+        // Flag is cleared by the next add that doesn't overflow.
+        hardware.registers[3] = 5;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[3], 60005);
+        assert_eq!(hardware.overflow_flag, false);
+    }
+
+    #[test]
+    fn instruction_add_wrapping() {
+        let mut hardware = Hardware::new(6);
+
+        let code = vec![0b1111_00_00001_00010u16, // Register 1 + Register 2
+                        0b1111_00_00011_01100u16, // Register 3 + Register 4 -> Memory 5
+                        0b1111_00_00001_10010u16, // Unsupported address type
+                        0u16,
+                        0u16,
+                        10000u16,
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 60000;
+        hardware.registers[2] = 10000;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], 4464);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[1], 60000);
+        assert_eq!(hardware.overflow_flag, false);
+
+        hardware.registers[3] = 12;
+        hardware.registers[4] = 5;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[5], 10012);
+        assert_eq!(hardware.program_counter, 2);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_skip_if_greater_signed() {
+        let mut hardware = Hardware::new(7);
+
+        let code = vec![0b0101_000001_000010u16, // Unsigned: Register 1 > Register 2
+                        0u16,
+                        0b1111_01_00001_00010u16, // Signed: Register 1 > Register 2
+                        0b1111_01_00010_00001u16, // Signed: Register 2 > Register 1
+                        0u16,
+                        0b1111_01_10001_00010u16, // Unsupported address type
+                        0u16,
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 0xFFFF; // -1
+        hardware.registers[2] = 1;
+
+        // Unsigned version skips.
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 2);
+
+        // Signed version doesn't.
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 3);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 5);
+        // Nothing should be changed.
+        assert_eq!(hardware.registers[1], 0xFFFF);
+        assert_eq!(hardware.registers[2], 1);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_fill() {
+        let mut hardware = Hardware::new(8);
+
+        let code = vec![0b1111_10_0_001_010_011u16, // Fill from Register 1, Register 2 words with Register 3
+                        0b1111_10_0_001_010_011u16,
+                        0b1111_10_1_001_010_011u16, // Unused bit is set
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 4;
+        hardware.registers[2] = 3;
+        hardware.registers[3] = 500;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[3..8], [0, 500, 500, 500, 0]);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[1], 4);
+        assert_eq!(hardware.registers[2], 3);
+        assert_eq!(hardware.registers[3], 500);
+
+        // Out of memory.
+        hardware.registers[2] = 5;
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+        assert_eq!(hardware.memory[3..8], [0, 500, 500, 500, 0]);
+
+        hardware.error_flag = false;
+        hardware.program_counter = 2;
+        hardware.registers[2] = 1;
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_subtract() {
+        let mut hardware = Hardware::new(19);
+
+        // -> means "points"
+        let code = vec![0b0011_000010_000111u16, // register two - register seven
+                        0b0011_010011_000110u16, // Register 3 -> memory 9 - register six
+                        0b0011_010011_010100u16, // Register 3 -> memory 7 - Register 4 -> memory 12
+                        0b0011_110000_000001u16, // Register 0 + PC (3) -> Memory 15 - Register 1
+                        0b0011_010101_110110u16, // Register 5 -> Memory 17 - Register 6 + PC (4) -> Memory 18
+                        0b0011_000101_000100u16, // Register 5 -> Memory 8 - Register 4
+                        0b0011_100000_000000u16, // Unsupported address type.
+                        // Data
+                        1200u16,
+                        0u16,
+                        2400u16,
+                        13u16, // Used as address
+                        0u16,
+                        1u16,
+                        12564u16,
+                        0u16,
+                        129u16,
+                        0u16,
+                        8u16,
+                        0u16,];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[2] = 256;
+        hardware.registers[7] = 100;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[7], 156);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[2], 256);
+
+        hardware.registers[3] = 9;
+        hardware.registers[6] = 1400;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[6], 1000);
+        assert_eq!(hardware.program_counter, 2);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[3], 9);
+        assert_eq!(hardware.memory[9], 2400);
+
+        hardware.registers[3] = 7;
+        hardware.registers[4] = 12;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[12], 1199);
+        assert_eq!(hardware.program_counter, 3);
+        // Nothing else should be changed.
+        assert_eq!(hardware.memory[7], 1200);
+        assert_eq!(hardware.registers[3], 7);
+        assert_eq!(hardware.registers[4], 12);
+
+        hardware.registers[0] = 12;
+        hardware.registers[1] = 29;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 100);
+        assert_eq!(hardware.program_counter, 4);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[0], 12);
+        assert_eq!(hardware.memory[15], 129);
+
+        hardware.registers[5] = 17;
+        hardware.registers[6] = 14;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[18], 8);
+        assert_eq!(hardware.program_counter, 5);
+        // Nothing else should be changed.
+        assert_eq!(hardware.memory[17], 8);
+        assert_eq!(hardware.registers[5], 17);
+        assert_eq!(hardware.registers[6], 14);
+
+        assert_eq!(hardware.overflow_flag, false);
+
+        hardware.registers[5] = 8;
+        hardware.registers[4] = 17;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[4], 0);
+        assert_eq!(hardware.program_counter, 6);
+        assert_eq!(hardware.overflow_flag, true);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[5], 8);
+        assert_eq!(hardware.memory[8], 0);
+
+        // Error: Register plus PC is not supported.
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_skip_if_equal() {
+        let mut hardware = Hardware::new(11);
+
+        let code = vec![0b0100_000000_000001u16, // Register 0 = Register 1
+                        0b0000000000000000u16,
+                        0b0100_010010_000110u16, // Register 2 -> Memory 8 = Register 6
+                        0b0100_110011_010101u16, // Register 3 + PC -> Memory 8 = Register 5 -> Memory 10
+                        0b0000000000000000u16,
+                        0b0100_000010_100011u16, // Unsupported address type
+                        0b0000000000000000u16,
+                        0b0000000000000000u16,
+                        0b0000000000000100u16, // 4
+                        0b0000000000000000u16,
+                        0b0000000000000100u16, // 4
+        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[0] = 2000;
+        hardware.registers[1] = 2000;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 2);
+
+        hardware.registers[2] = 8;
+        hardware.registers[6] = 7;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 3);
+
+        hardware.registers[3] = 5;
+        hardware.registers[5] = 10;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 5);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_skip_if_greater() {
+        let mut hardware = Hardware::new(11);
+
+        let code = vec![0b0101_000000_000001u16, // Register 0 > Register 1
+                        0b0000000000000000u16,
+                        0b0101_010010_000110u16, // Register 2 -> Memory 8 > Register 6
+                        0b0101_110011_010101u16, // Register 3 + PC -> Memory 8 > Register 5 -> Memory 10
+                        0b0000000000000000u16,
+                        0b0101_000010_100011u16, // Unsupported address type
+                        0b0000000000000000u16,
+                        0b0000000000000000u16,
+                        0b0100000000000101u16, // 16389
+                        0b0000000000000000u16,
+                        0b0001001001001101u16, // 4685
+        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[0] = 2001;
+        hardware.registers[1] = 2000;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 2);
+
+        hardware.registers[2] = 8;
+        hardware.registers[6] = 16390;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 3);
+
+        hardware.registers[3] = 5;
+        hardware.registers[5] = 10;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 5);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_set_max_constant() {
+        let mut hardware = Hardware::new(1);
+
+        // Every bit of the instruction is set, except the operation code.
+        hardware.load(&vec![0b0110_111_111111111u16], 0).unwrap();
+        hardware.registers[6] = 3;
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[7], 511);
+        assert_eq!(hardware.registers[6], 3);
+        assert_eq!(hardware.program_counter, 1);
+    }
+
+    #[test]
+    fn instruction_set() {
+        let mut hardware = Hardware::new(3);
+
+        let code = vec![0b0110_010_001001101u16,
+                        0b0110_111_111111111u16,
+                        0b0110_010_000000000u16];
+        hardware.load(&code, 0).unwrap();
+
+
+        hardware.registers[4] = 100;
+        hardware.registers[3] = 120;
+        hardware.registers[1] = 140;
+        hardware.registers[0] = 1000;
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], 0b0000_000_001001101u16);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should change.
+        assert_eq!(hardware.registers[4], 100);
+        assert_eq!(hardware.registers[3], 120);
+        assert_eq!(hardware.registers[1], 140);
+        assert_eq!(hardware.registers[0], 1000);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[7], 0b0000_000_111111111u16);
+        assert_eq!(hardware.program_counter, 2);
+        // Nothing else should change.
+        assert_eq!(hardware.registers[2], 0b0000_000_001001101u16);
+        assert_eq!(hardware.registers[4], 100);
+        assert_eq!(hardware.registers[3], 120);
+        assert_eq!(hardware.registers[1], 140);
+        assert_eq!(hardware.registers[0], 1000);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], 0u16);
+        assert_eq!(hardware.program_counter, 3);
+        // Nothing else should change.
+        assert_eq!(hardware.registers[7], 0b0000_000_111111111u16);
+        assert_eq!(hardware.registers[4], 100);
+        assert_eq!(hardware.registers[3], 120);
+        assert_eq!(hardware.registers[1], 140);
+        assert_eq!(hardware.registers[0], 1000);
+    }
+
+    #[test]
+    fn instruction_load_immediate() {
+        let mut hardware = Hardware::new(5);
+
+        let code = vec![0b1011_011_000000000u16, // Load the next word into R3
+                        40000u16,
+                        0b1011_111_000000000u16, // Load the next word into R7
+                        65535u16,
+                        0b1011_001_000000000u16]; // Constant is out of memory
+        hardware.load(&code, 0).unwrap();
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[3], 40000);
+        assert_eq!(hardware.program_counter, 2);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[7], 65535);
+        assert_eq!(hardware.program_counter, 4);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[3], 40000);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_bit_field_extract() {
+        let mut hardware = Hardware::new(3);
+
+        let code = vec![0b0111_001_0110_00100u16, // Extract 4 bits of R1 from bit 6
+                        0b0111_010_0000_10000u16, // Extract all 16 bits of R2
+                        0b0111_010_1100_01000u16]; // Out of range: 8 bits from bit 12
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 0b1011_0110_1100_0000u16;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 0b1011u16);
+        assert_eq!(hardware.program_counter, 1);
+
+        hardware.registers[2] = 0xFFFF;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], 0xFFFF);
+        assert_eq!(hardware.program_counter, 2);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_bit_field_insert() {
+        let mut hardware = Hardware::new(3);
+
+        let code = vec![0b1000_010_0100_00011u16, // Insert 3 bits of R0 into R2 from bit 4
+                        0b1000_011_0000_10000u16, // Insert all 16 bits of R0 into R3
+                        0b1000_010_0000_00000u16]; // Out of range: zero width
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[0] = 0b1111_1101u16;
+        hardware.registers[2] = 0xFFFF;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], 0b1111_1111_1101_1111u16);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should change.
+        assert_eq!(hardware.registers[0], 0b1111_1101u16);
+
+        hardware.registers[3] = 0x1234;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[3], 0b1111_1101u16);
+        assert_eq!(hardware.program_counter, 2);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_shift_left() {
+        let mut hardware = Hardware::new(6);
+
+        let code = vec![0b1001_000001_000010u16, // Register 1 << Register 2
+                        0b1001_010011_000100u16, // Register 3 -> Memory 5 << Register 4
+                        0b1001_000001_100010u16, // Unsupported address type
+                        0u16,
+                        0u16,
+                        0b0000_0000_1111_1111u16,
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 1;
+        hardware.registers[2] = 8;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], 256);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[1], 1);
+
+        // Bits are shifted out.
+        hardware.registers[3] = 5;
+        hardware.registers[4] = 12;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[4], 0b1111_0000_0000_0000u16);
+        assert_eq!(hardware.program_counter, 2);
+        assert_eq!(hardware.memory[5], 0b0000_0000_1111_1111u16);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn instruction_swap() {
+        let mut hardware = Hardware::new(6);
+
+        let code = vec![0b1100_000001_000010u16, // Register 1 <-> Register 2
+                        0b1100_000011_010100u16, // Register 3 <-> Memory 5
+                        0b1100_010100_010100u16, // Memory 5 <-> Memory 5
+                        0b1100_000001_100010u16, // Unsupported address type
+                        0u16,
+                        700u16,
+                        ];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[1] = 5;
+        hardware.registers[2] = 9;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 9);
+        assert_eq!(hardware.registers[2], 5);
+        assert_eq!(hardware.program_counter, 1);
+
+        hardware.registers[3] = 42;
+        hardware.registers[4] = 5;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[3], 700);
+        assert_eq!(hardware.memory[5], 42);
+        assert_eq!(hardware.program_counter, 2);
+
+        // Swapping an address with itself changes nothing.
+        hardware.clock().unwrap();
+        assert_eq!(hardware.memory[5], 42);
+        assert_eq!(hardware.program_counter, 3);
+
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+        // Nothing should be changed.
+        assert_eq!(hardware.registers[1], 9);
+        assert_eq!(hardware.registers[2], 5);
+    }
+
+    #[test]
+    fn instruction_compare() {
+        let mut hardware = Hardware::new(9);
+
+        let code = vec![0b1101_000001_000010u16, // Register 1 ? Register 2
+                        0b1101_000001_000010u16,
+                        0b1101_000001_000010u16,
+                        0b1101_010011_000100u16, // Memory 7 ? Register 4
+                        0b1101_000011_010100u16, // Register 3 ? Memory 8
+                        0b1101_000001_100010u16, // Unsupported address type
+                        0u16,
+                        300u16,
+                        500u16,
+                        ];
         hardware.load(&code, 0).unwrap();
 
-        hardware.registers[2] = 256;
-        hardware.registers[7] = 100;
+        // Equal
+        hardware.registers[1] = 12;
+        hardware.registers[2] = 12;
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[7], 156);
+        assert_eq!(hardware.registers[2], 0);
+        assert_eq!(hardware.registers[1], 12);
         assert_eq!(hardware.program_counter, 1);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[2], 256);
 
-        hardware.registers[3] = 9;
-        hardware.registers[6] = 1400;
+        // Greater
+        hardware.registers[2] = 11;
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[6], 1000);
-        assert_eq!(hardware.program_counter, 2);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[3], 9);
-        assert_eq!(hardware.memory[9], 2400);
+        assert_eq!(hardware.registers[2], 1);
 
-        hardware.registers[3] = 7;
-        hardware.registers[4] = 12;
+        // Less
+        hardware.registers[2] = 13;
         hardware.clock().unwrap();
-        assert_eq!(hardware.memory[12], 1199);
-        assert_eq!(hardware.program_counter, 3);
-        // Nothing else should be changed.
-        assert_eq!(hardware.memory[7], 1200);
-        assert_eq!(hardware.registers[3], 7);
-        assert_eq!(hardware.registers[4], 12);
+        assert_eq!(hardware.registers[2], 2);
 
-        hardware.registers[0] = 12;
-        hardware.registers[1] = 29;
+        // Memory as the first operand.
+        hardware.registers[3] = 7;
+        hardware.registers[4] = 300;
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[1], 100);
-        assert_eq!(hardware.program_counter, 4);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[0], 12);
-        assert_eq!(hardware.memory[15], 129);
+        assert_eq!(hardware.registers[4], 0);
 
-        hardware.registers[5] = 17;
-        hardware.registers[6] = 14;
+        // Memory as the second operand.
+        hardware.registers[3] = 700;
+        hardware.registers[4] = 8;
         hardware.clock().unwrap();
-        assert_eq!(hardware.memory[18], 8);
+        assert_eq!(hardware.memory[8], 1);
         assert_eq!(hardware.program_counter, 5);
-        // Nothing else should be changed.
-        assert_eq!(hardware.memory[17], 8);
-        assert_eq!(hardware.registers[5], 17);
-        assert_eq!(hardware.registers[6], 14);
-
-        hardware.registers[5] = 8;
-        hardware.registers[4] = 17;
-        hardware.clock().unwrap();
-        assert_eq!(hardware.registers[4], 0);
-        assert_eq!(hardware.program_counter, 6);
-        // Nothing else should be changed.
-        assert_eq!(hardware.registers[5], 8);
-        assert_eq!(hardware.memory[8], 0);
 
-        // Error: Register plus PC is not supported.
         let clock_result = hardware.clock();
         assert_eq!(clock_result.is_err(), true);
     }
 
     #[test]
-    fn instruction_skip_if_equal() {
-        let mut hardware = Hardware::new(11);
+    fn instruction_take() {
+        let mut hardware = Hardware::new(6);
 
-        let code = vec![0b0100_000000_000001u16, // Register 0 = Register 1
-                        0b0000000000000000u16,
-                        0b0100_010010_000110u16, // Register 2 -> Memory 8 = Register 6
-                        0b0100_110011_010101u16, // Register 3 + PC -> Memory 8 = Register 5 -> Memory 10
-                        0b0000000000000000u16,
-                        0b0100_000010_100011u16, // Unsupported address type
-                        0b0000000000000000u16,
-                        0b0000000000000000u16,
-                        0b0000000000000100u16, // 4
-                        0b0000000000000000u16,
-                        0b0000000000000100u16, // 4
-        ];
+        let code = vec![0b1110_000001_010010u16, // Register 1 -> Register 2 -> Memory 5
+                        0b1110_010010_000011u16, // Register 2 -> Memory 5 -> Register 3
+                        0b1110_000001_100010u16, // Unsupported address type
+                        0b1110_100001_000010u16, // Unsupported address type
+                        0u16,
+                        0u16,
+                        ];
         hardware.load(&code, 0).unwrap();
 
-        hardware.registers[0] = 2000;
-        hardware.registers[1] = 2000;
+        hardware.registers[1] = 42;
+        hardware.registers[2] = 5;
         hardware.clock().unwrap();
-        assert_eq!(hardware.program_counter, 2);
+        assert_eq!(hardware.memory[5], 42);
+        assert_eq!(hardware.registers[1], 0);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[2], 5);
 
-        hardware.registers[2] = 8;
-        hardware.registers[6] = 7;
         hardware.clock().unwrap();
-        assert_eq!(hardware.program_counter, 3);
+        assert_eq!(hardware.registers[3], 42);
+        assert_eq!(hardware.memory[5], 0);
+        assert_eq!(hardware.program_counter, 2);
 
-        hardware.registers[3] = 5;
-        hardware.registers[5] = 10;
-        hardware.clock().unwrap();
-        assert_eq!(hardware.program_counter, 5);
+        hardware.registers[1] = 7;
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+        assert_eq!(hardware.registers[1], 7);
 
+        hardware.error_flag = false;
         let clock_result = hardware.clock();
         assert_eq!(clock_result.is_err(), true);
     }
 
     #[test]
-    fn instruction_skip_if_greater() {
-        let mut hardware = Hardware::new(11);
+    fn instruction_shift_right() {
+        let mut hardware = Hardware::new(6);
 
-        let code = vec![0b0101_000000_000001u16, // Register 0 > Register 1
-                        0b0000000000000000u16,
-                        0b0101_010010_000110u16, // Register 2 -> Memory 8 > Register 6
-                        0b0101_110011_010101u16, // Register 3 + PC -> Memory 8 > Register 5 -> Memory 10
-                        0b0000000000000000u16,
-                        0b0101_000010_100011u16, // Unsupported address type
-                        0b0000000000000000u16,
-                        0b0000000000000000u16,
-                        0b0100000000000101u16, // 16389
-                        0b0000000000000000u16,
-                        0b0001001001001101u16, // 4685
-        ];
+        let code = vec![0b1010_000001_000010u16, // Register 1 >> Register 2
+                        0b1010_000011_010100u16, // Register 3 >> Register 4 -> Memory 5
+                        0b1010_100001_000010u16, // Unsupported address type
+                        0u16,
+                        0u16,
+                        4u16,
+                        ];
         hardware.load(&code, 0).unwrap();
 
-        hardware.registers[0] = 2001;
-        hardware.registers[1] = 2000;
-        hardware.clock().unwrap();
-        assert_eq!(hardware.program_counter, 2);
-
-        hardware.registers[2] = 8;
-        hardware.registers[6] = 16390;
+        hardware.registers[1] = 1;
+        hardware.registers[2] = 1;
         hardware.clock().unwrap();
-        assert_eq!(hardware.program_counter, 3);
+        assert_eq!(hardware.registers[2], 0);
+        assert_eq!(hardware.program_counter, 1);
+        // Nothing else should be changed.
+        assert_eq!(hardware.registers[1], 1);
 
-        hardware.registers[3] = 5;
-        hardware.registers[5] = 10;
+        hardware.registers[3] = 0xFF00;
+        hardware.registers[4] = 5;
         hardware.clock().unwrap();
-        assert_eq!(hardware.program_counter, 5);
+        assert_eq!(hardware.memory[5], 0x0FF0);
+        assert_eq!(hardware.program_counter, 2);
 
         let clock_result = hardware.clock();
         assert_eq!(clock_result.is_err(), true);
     }
 
+    struct MockIoHandler {
+        writes: Rc<RefCell<Vec<(u16, u16)>>>,
+    }
+
+    impl IoHandler for MockIoHandler {
+        fn read(&mut self, address: u16) -> u16 {
+            return address + 1000;
+        }
+
+        fn write(&mut self, address: u16, value: u16) {
+            self.writes.borrow_mut().push((address, value));
+        }
+    }
+
     #[test]
-    fn instruction_set() {
-        let mut hardware = Hardware::new(3);
+    fn map_io() {
+        let mut hardware = Hardware::new(10);
 
-        let code = vec![0b0110_010_001001101u16,
-                        0b0110_111_111111111u16,
-                        0b0110_010_000000000u16];
+        let writes = Rc::new(RefCell::new(Vec::new()));
+        hardware.map_io(6, 8, Box::new(MockIoHandler { writes: Rc::clone(&writes) })).unwrap();
+
+        let code = vec![0b0001_000001_010010u16, // Copy R1 => Register 2 -> Memory 6
+                        0b0001_010011_000100u16, // Register 3 -> Memory 7 => R4
+                        0b0010_000001_010011u16, // R1 + Register 3 -> Memory 7
+                        0b0001_000001_010101u16, // Copy R1 => Register 5 -> Memory 8 (not mapped)
+                        0b0000_000001_000010u16, // Jump to R2 (6)
+                        ];
         hardware.load(&code, 0).unwrap();
 
+        hardware.registers[1] = 42;
+        hardware.registers[2] = 6;
+        hardware.registers[3] = 7;
+        hardware.registers[5] = 8;
 
-        hardware.registers[4] = 100;
-        hardware.registers[3] = 120;
-        hardware.registers[1] = 140;
-        hardware.registers[0] = 1000;
+        hardware.clock().unwrap();
+        assert_eq!(*writes.borrow(), vec![(6, 42)]);
+        assert_eq!(hardware.memory[6], 0);
 
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[2], 0b0000_000_001001101u16);
-        assert_eq!(hardware.program_counter, 1);
-        // Nothing else should change.
-        assert_eq!(hardware.registers[4], 100);
-        assert_eq!(hardware.registers[3], 120);
-        assert_eq!(hardware.registers[1], 140);
-        assert_eq!(hardware.registers[0], 1000);
+        assert_eq!(hardware.registers[4], 1007);
 
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[7], 0b0000_000_111111111u16);
-        assert_eq!(hardware.program_counter, 2);
-        // Nothing else should change.
-        assert_eq!(hardware.registers[2], 0b0000_000_001001101u16);
-        assert_eq!(hardware.registers[4], 100);
-        assert_eq!(hardware.registers[3], 120);
-        assert_eq!(hardware.registers[1], 140);
-        assert_eq!(hardware.registers[0], 1000);
+        assert_eq!(*writes.borrow(), vec![(6, 42), (7, 1049)]);
 
         hardware.clock().unwrap();
-        assert_eq!(hardware.registers[2], 0u16);
-        assert_eq!(hardware.program_counter, 3);
-        // Nothing else should change.
-        assert_eq!(hardware.registers[7], 0b0000_000_111111111u16);
-        assert_eq!(hardware.registers[4], 100);
-        assert_eq!(hardware.registers[3], 120);
-        assert_eq!(hardware.registers[1], 140);
-        assert_eq!(hardware.registers[0], 1000);
+        assert_eq!(hardware.memory[8], 42);
+        assert_eq!(writes.borrow().len(), 2);
+
+        // Executing from the mapped region is an error.
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 6);
+        let clock_result = hardware.clock();
+        assert_eq!(clock_result.is_err(), true);
+    }
+
+    #[test]
+    fn map_io_errors() {
+        let mut hardware = Hardware::new(10);
+
+        let handler = || Box::new(MockIoHandler { writes: Rc::new(RefCell::new(Vec::new())) });
+
+        // Empty region.
+        assert_eq!(hardware.map_io(4, 4, handler()).is_err(), true);
+        // Out of memory.
+        assert_eq!(hardware.map_io(8, 11, handler()).is_err(), true);
+
+        hardware.map_io(2, 5, handler()).unwrap();
+        hardware.map_io(5, 10, handler()).unwrap();
+
+        // Overlap
+        assert_eq!(hardware.map_io(0, 3, handler()).is_err(), true);
+        assert_eq!(hardware.map_io(4, 6, handler()).is_err(), true);
+
+        hardware.map_io(0, 2, handler()).unwrap();
     }
 
     struct MockSyscall {
@@ -809,6 +2500,26 @@ mod tests {
                 return;
             }
 
+            if cpu_state.get_register(0) == 2 {
+                // Reports the flags in R1, and clears them.
+                cpu_state.set_register(1, (cpu_state.get_overflow_flag() as u16) |
+                                          ((cpu_state.get_underflow_flag() as u16) << 1));
+                cpu_state.set_overflow_flag(false);
+                cpu_state.set_underflow_flag(false);
+                return;
+            }
+
+            if cpu_state.get_register(0) == 4 {
+                cpu_state.set_register(1, cpu_state.get_call_stack_depth());
+                return;
+            }
+
+            if cpu_state.get_register(0) == 3 {
+                // Jumps to the address in R1.
+                cpu_state.set_program_counter(cpu_state.get_register(1));
+                return;
+            }
+
             assert_eq!(cpu_state.get_error_flag(), false);
             assert_eq!(cpu_state.get_register(0), 17);
             assert_eq!(cpu_state.get_register(1), 128);
@@ -852,4 +2563,120 @@ mod tests {
         assert_eq!(hardware.error_flag, true);
     }
 
+    #[test]
+    fn instruction_syscall_boxed() {
+        let mut hardware = Hardware::new(3);
+
+        hardware.register_sys_callback_boxed(Box::new(MockSyscall {}));
+
+        let code = vec![0b0000000000_000001u16,
+                        0b0000000000_000001u16];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[0] = 17;
+        hardware.registers[1] = 128;
+        hardware.registers[7] = 5;
+
+        hardware.clock().unwrap();
+
+        assert_eq!(hardware.program_counter, 1);
+        assert_eq!(hardware.registers[0], 0);
+        assert_eq!(hardware.registers[3], 12);
+        assert_eq!(hardware.registers[7], 2);
+
+        hardware.registers[0] = 1;
+        let clock_result = hardware.clock();
+
+        assert_eq!(hardware.program_counter, 2);
+        assert_eq!(clock_result.is_err(), true);
+        assert_eq!(hardware.error_flag, true);
+    }
+
+    #[test]
+    fn instruction_syscall_flags() {
+        let mut hardware = Hardware::new(3);
+
+        hardware.register_sys_callback_boxed(Box::new(MockSyscall {}));
+
+        let code = vec![0b0010_000001_000010u16, // Saturating add
+                        0b0000000000_000001u16,
+                        0b0000000000_000001u16];
+        hardware.load(&code, 0).unwrap();
+
+        hardware.registers[0] = 2;
+        hardware.registers[1] = 60000;
+        hardware.registers[2] = 60000;
+        hardware.clock().unwrap();
+        hardware.clock().unwrap();
+
+        assert_eq!(hardware.registers[1], 0b01);
+        // Callback cleared the flag.
+        assert_eq!(hardware.overflow_flag, false);
+
+        hardware.underflow_flag = true;
+        hardware.clock().unwrap();
+
+        assert_eq!(hardware.registers[1], 0b10);
+        assert_eq!(hardware.underflow_flag, false);
+    }
+
+    #[test]
+    fn instruction_syscall_set_program_counter() {
+        let mut hardware = Hardware::new(12);
+
+        hardware.register_sys_callback_boxed(Box::new(MockSyscall {}));
+
+        let code = vec![0b0000000000_000001u16];
+        hardware.load(&code, 0).unwrap();
+        hardware.load(&vec![0b0110_010_000000111u16], 10).unwrap(); // Set R2 to 7
+
+        hardware.registers[0] = 3;
+        hardware.registers[1] = 10;
+        hardware.clock().unwrap();
+
+        assert_eq!(hardware.program_counter, 10);
+
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[2], 7);
+        assert_eq!(hardware.program_counter, 11);
+    }
+
+    #[test]
+    fn instruction_syscall_retry() {
+        let mut hardware = Hardware::new(2);
+
+        hardware.register_sys_callback_boxed(Box::new(MockSyscall {}));
+        hardware.load(&vec![0b0000000000_000001u16], 0).unwrap();
+
+        // Sys call sets the PC to its own address, so it's executed again.
+        hardware.registers[0] = 3;
+        hardware.registers[1] = 0;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 0);
+
+        // Without setting the PC, it continues to the next instruction.
+        hardware.registers[0] = 4;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.program_counter, 1);
+    }
+
+    #[test]
+    fn instruction_syscall_call_stack_depth() {
+        let mut hardware = Hardware::new(2);
+
+        hardware.register_sys_callback_boxed(Box::new(MockSyscall {}));
+
+        // Calls itself.
+        hardware.load(&vec![0b0000_000011_000000u16], 0).unwrap();
+        hardware.load(&vec![0b0000000000_000001u16], 1).unwrap();
+
+        hardware.clock().unwrap();
+        hardware.program_counter = 0;
+        hardware.clock().unwrap();
+
+        hardware.registers[0] = 4;
+        hardware.program_counter = 1;
+        hardware.clock().unwrap();
+        assert_eq!(hardware.registers[1], 2);
+    }
 }
@@ -16,11 +16,15 @@
 
 use std::hash::{Hash, Hasher};
 
-#[derive(PartialEq, Eq)]
-enum OperationType {
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum OperationType {
     NoOperand,
     SingleOperand,
     DoubleOperand,
+    // Double operand operations that share the last operation code. Operands are
+    // five bits: two bits type and three bits register number.
+    // Format: 1111 oo ttrrr ttrrr
+    ExtendedDoubleOperand,
 }
 
 /// This structures holds an entry of the memory that represents
@@ -28,35 +32,55 @@ enum OperationType {
 /// in a HashMap.
 pub struct OperationCode {
     value: u16,
+    // Type and mask are decoded once, when the instance is created.
+    operation_type: OperationType,
+    mask: u16,
 }
 
 impl OperationCode {
     pub fn new(value: u16) -> OperationCode {
+        let operation_type = get_operation_type(value);
+
         OperationCode {
             value: value,
+            operation_type: operation_type,
+            mask: get_operation_mask(operation_type),
         }
     }
 
-    /// Returns operation type of the specified value.
-    fn get_operation_type(&self, instruction: u16) -> OperationType {
+    /// Returns operation type of this operation.
+    pub fn operation_type(&self) -> OperationType {
+        return self.operation_type;
+    }
 
-        if instruction & 0b1111111111000000u16 == 0b0000000000000000u16 {
-            return OperationType::NoOperand;
-        } else if instruction & 0b1111000000000000u16 == 0b0000000000000000u16 {
-            return OperationType::SingleOperand;
-        } else {
-            return OperationType::DoubleOperand;
-        }
+    /// Returns the operation code without its operands.
+    pub fn code(&self) -> u16 {
+        return self.value & self.mask;
+    }
+}
+
+/// Returns operation type of the specified value.
+fn get_operation_type(instruction: u16) -> OperationType {
+
+    if instruction & 0b1111111111000000u16 == 0b0000000000000000u16 {
+        return OperationType::NoOperand;
+    } else if instruction & 0b1111000000000000u16 == 0b0000000000000000u16 {
+        return OperationType::SingleOperand;
+    } else if instruction & 0b1111000000000000u16 == 0b1111000000000000u16 {
+        return OperationType::ExtendedDoubleOperand;
+    } else {
+        return OperationType::DoubleOperand;
     }
+}
 
-    /// Gets a mask that extracts operation code from the specified value.
-    fn get_operation_mask(&self, operation_type: OperationType) -> u16 {
+/// Gets a mask that extracts operation code from the specified value.
+fn get_operation_mask(operation_type: OperationType) -> u16 {
 
-        match operation_type {
-            OperationType::NoOperand => return 0b0000000000111111u16,
-            OperationType::SingleOperand => return 0b0000111111000000u16,
-            OperationType::DoubleOperand => return 0b1111000000000000u16,
-        }
+    match operation_type {
+        OperationType::NoOperand => return 0b0000000000111111u16,
+        OperationType::SingleOperand => return 0b0000111111000000u16,
+        OperationType::DoubleOperand => return 0b1111000000000000u16,
+        OperationType::ExtendedDoubleOperand => return 0b1111110000000000u16,
     }
 }
 
@@ -64,19 +88,7 @@ impl PartialEq for OperationCode {
     fn eq(&self, other: &OperationCode) -> bool {
 
         // Comparing types, then comparing operation type without its operands.
-        let my_type = self.get_operation_type(self.value);
-        let other_type = self.get_operation_type(other.value);
-
-        if my_type != other_type {
-            return false;
-        }
-
-        let mask = self.get_operation_mask(my_type);
-        if self.value & mask == other.value & mask {
-            return true;
-        }
-
-        return false;
+        return self.operation_type == other.operation_type && self.code() == other.code();
     }
 }
 
@@ -85,9 +97,47 @@ impl Eq for OperationCode {}
 impl Hash for OperationCode {
     fn hash<H: Hasher>(&self, state: &mut H) {
 
-        // Extracting operation code without its operands, and hash that.
-        let mask = self.get_operation_mask(self.get_operation_type(self.value));
+        // Hashing operation code without its operands.
+        self.code().hash(state);
+    }
+}
 
-        (self.value & mask).hash(state);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_operation_different_operands() {
+        // COPY R1 M6 and COPY M2 RPM3
+        let first = OperationCode::new(0b0001_000001_010110u16);
+        let second = OperationCode::new(0b0001_010010_110011u16);
+
+        assert_eq!(first.operation_type(), OperationType::DoubleOperand);
+        assert_eq!(first.code(), 0b0001_000000_000000u16);
+        assert_eq!(first == second, true);
+
+        // JUMP R1 and JUMP M7
+        let first = OperationCode::new(0b0000_000001_000001u16);
+        let second = OperationCode::new(0b0000_000001_010111u16);
+        assert_eq!(first.operation_type(), OperationType::SingleOperand);
+        assert_eq!(first == second, true);
+
+        // COPY and ADD
+        let first = OperationCode::new(0b0001_000001_010110u16);
+        let second = OperationCode::new(0b0010_000001_010110u16);
+        assert_eq!(first == second, false);
+
+        // Extended operations with different codes.
+        let first = OperationCode::new(0b1111_00_00001_00010u16);
+        let second = OperationCode::new(0b1111_01_00001_00010u16);
+        assert_eq!(first.operation_type(), OperationType::ExtendedDoubleOperand);
+        assert_eq!(first.code(), 0b1111_00_00000_00000u16);
+        assert_eq!(first == second, false);
+
+        // HALT and SYSCALL
+        let first = OperationCode::new(0b0000000000_000011u16);
+        let second = OperationCode::new(0b0000000000_000001u16);
+        assert_eq!(first.operation_type(), OperationType::NoOperand);
+        assert_eq!(first == second, false);
     }
 }
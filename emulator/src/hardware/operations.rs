@@ -16,49 +16,93 @@
 
 /// This module defines micro-operations of the CPU.
 
-use std::collections::HashMap;
 use hardware::Hardware;
 use hardware::operation_code::OperationCode;
 use CPUState;
+use ClockError;
 
 
+type OperationFunction = fn(&mut Hardware, u16) -> Result<(), ClockError>;
+
+/// Cost of a clock that doesn't execute any instruction, e.g. when the CPU is halted.
+pub const IDLE_CLOCK_COST: u16 = 1;
+
 pub struct Operations {
-    functions: HashMap<OperationCode, fn(&mut Hardware, u16) -> Result<(), String>>,
 }
 
 impl Operations {
     pub fn new() -> Operations {
-        let mut map: HashMap<OperationCode, fn(&mut Hardware, u16) -> Result<(), String>> =
-            HashMap::new();
-
-        // No operand operations
-        map.insert(OperationCode::new(0b0000000000_000000u16), nop);
-        map.insert(OperationCode::new(0b0000000000_000001u16), syscall);
-        map.insert(OperationCode::new(0b0000000000_000010u16), return_subroutine);
-
-        // Single operand operations
-        map.insert(OperationCode::new(0b0000_000001_000000u16), jump);
-        map.insert(OperationCode::new(0b0000_000010_000000u16), skip_if_zero);
-        map.insert(OperationCode::new(0b0000_000011_000000u16), subroutine);
-
-        // Double operand operations
-        map.insert(OperationCode::new(0b0001_000000000000u16), copy);
-        map.insert(OperationCode::new(0b0010_000000000000u16), add);
-        map.insert(OperationCode::new(0b0011_000000000000u16), subtract);
-        map.insert(OperationCode::new(0b0100_000000000000u16), skip_if_equal);
-        map.insert(OperationCode::new(0b0101_000000000000u16), skip_if_greater);
-        map.insert(OperationCode::new(0b0110_000000000000u16), set);
-
         Operations {
-            functions: map,
         }
     }
 
-    pub fn get_function(&self, instruction: u16) -> Result<fn(&mut Hardware, u16) -> Result<(), String>, String> {
-        match self.functions.get(&OperationCode::new(instruction)) {
-            Some(&function) => return Ok(function),
-            None => return Err(format!("Unknown instruction: [{:b}]", instruction)),
-        }
+    /// Decodes the instruction and returns the function that executes it.
+    /// This is called on every clock, so it's a plain match on the operation
+    /// code rather than a lookup. Two operations with the same code would make
+    /// one of the arms unreachable, so that's denied.
+    #[deny(unreachable_patterns)]
+    pub fn get_function(&self, instruction: u16) -> Result<OperationFunction, ClockError> {
+        let function: OperationFunction = match OperationCode::new(instruction).code() {
+            // No operand operations
+            0b0000000000_000000u16 => nop,
+            0b0000000000_000001u16 => syscall,
+            0b0000000000_000010u16 => return_subroutine,
+            0b0000000000_000011u16 => halt,
+
+            // Single operand operations
+            0b0000_000001_000000u16 => jump,
+            0b0000_000010_000000u16 => skip_if_zero,
+            0b0000_000011_000000u16 => subroutine,
+            0b0000_000100_000000u16 => popcount,
+            0b0000_000101_000000u16 => count_leading_zeros,
+            0b0000_000110_000000u16 => count_trailing_zeros,
+            0b0000_000111_000000u16 => dec_skip_if_zero,
+            0b0000_001000_000000u16 => increment,
+            0b0000_001001_000000u16 => decrement,
+            0b0000_001010_000000u16 => push,
+            0b0000_001011_000000u16 => pop,
+            0b0000_001100_000000u16 => jump_link,
+
+            // Double operand operations
+            0b0001_000000000000u16 => copy,
+            0b0010_000000000000u16 => add,
+            0b0011_000000000000u16 => subtract,
+            0b0100_000000000000u16 => skip_if_equal,
+            0b0101_000000000000u16 => skip_if_greater,
+            0b0110_000000000000u16 => set,
+            0b0111_000000000000u16 => bit_field_extract,
+            0b1000_000000000000u16 => bit_field_insert,
+            0b1001_000000000000u16 => shift_left,
+            0b1010_000000000000u16 => shift_right,
+            0b1011_000000000000u16 => load_immediate,
+            0b1100_000000000000u16 => swap,
+            0b1101_000000000000u16 => compare,
+            0b1110_000000000000u16 => take,
+
+            // Extended double operand operations
+            0b1111_00_0000000000u16 => add_wrapping,
+            0b1111_01_0000000000u16 => skip_if_greater_signed,
+            0b1111_10_0000000000u16 => fill,
+
+            _ => return Err(ClockError::UnknownInstruction(instruction)),
+        };
+
+        return Ok(function);
+    }
+
+    /// Returns the cost of executing the instruction. Hosts can charge the
+    /// programs by it, e.g. to make the expensive instructions cost more credit.
+    #[deny(unreachable_patterns)]
+    pub fn get_cost(&self, instruction: u16) -> u16 {
+        return match OperationCode::new(instruction).code() {
+            // Sys call
+            0b0000000000_000001u16 => 5,
+            // Subroutine
+            0b0000_000011_000000u16 => 2,
+            // Load immediate reads two words.
+            0b1011_000000000000u16 => 2,
+            _ => 1,
+        };
     }
 }
 
@@ -75,7 +119,7 @@ enum Address {
 /// Returns the real address that specified "address" is pointing to.
 /// For example, "address" points to where the real address stored.
 /// Addresses are 6 bits, so the first 2 bits will be ignored.
-fn get_true_address(hardware: &Hardware, address: u8) -> Result<Address, String> {
+fn get_true_address(hardware: &Hardware, address: u8) -> Result<Address, ClockError> {
 
     // Out addresses is 6 bit, so the first two bits are ignored.
     // Second two bits shows address type, and the rest (4 bits)
@@ -85,7 +129,7 @@ fn get_true_address(hardware: &Hardware, address: u8) -> Result<Address, String>
     let register_number = address & 0b0000_1111u8;
 
     if register_number > 7 {
-        return Err(format!("Invalid register number. [{}]", register_number));
+        return Err(ClockError::InvalidOperand(format!("Invalid register number. [{}]", register_number)));
     }
 
     if address_type == 0b00_00_0000u8 {
@@ -97,9 +141,10 @@ fn get_true_address(hardware: &Hardware, address: u8) -> Result<Address, String>
         let memory_address = hardware.registers[register_number as usize];
 
         if memory_address as usize >= hardware.memory.len() {
-            return Err(format!(
-                "Address is out of memory. Address was [{}] stored in register [{}].",
-                memory_address, register_number));
+            return Err(ClockError::AddressOutOfMemory {
+                address: memory_address,
+                register: register_number,
+            });
         }
 
         return Ok(Address::Memory(memory_address));
@@ -118,22 +163,36 @@ fn get_true_address(hardware: &Hardware, address: u8) -> Result<Address, String>
             hardware.registers[register_number as usize].overflowing_add(hardware.program_counter);
 
         if is_overflowed {
-            return Err(format!(
+            return Err(ClockError::InvalidOperand(format!(
                 "Memory address overflow. PC ({}) + Register{} ({})",
                 hardware.program_counter, register_number,
-                hardware.registers[register_number as usize]))
+                hardware.registers[register_number as usize])))
         }
 
         if memory_address as usize >= hardware.memory.len() {
-            return Err(format!(
-                "Address is out of memory. Address was [{}] stored in register [{}].",
-                memory_address, register_number));
+            return Err(ClockError::AddressOutOfMemory {
+                address: memory_address,
+                register: register_number,
+            });
         }
 
         return Ok(Address::Memory(memory_address));
     }
 }
 
+/// Same as get_true_address, but if it fails, the error is wrapped with the
+/// role of the operand and the instruction, so it's clear which one is faulty.
+///
+/// @operand: Role of the operand, i.e. "source" or "destination".
+fn get_operand_address(hardware: &Hardware, address: u8, operand: &'static str, instruction: u16)
+                       -> Result<Address, ClockError> {
+    return get_true_address(hardware, address).map_err(|error| ClockError::OperandFault {
+        operand: operand,
+        instruction: instruction,
+        cause: Box::new(error),
+    });
+}
+
 
 /// Extracts address from a one-operand instruction.
 fn extract_one_operand_address(instruction: u16) -> u8 {
@@ -141,17 +200,33 @@ fn extract_one_operand_address(instruction: u16) -> u8 {
 }
 
 /// Extracts addresses from a two-operand instruction.
+/// Extended instructions have five bits operands, which are converted to
+/// the usual six bits addresses.
 fn extract_two_operand_address(instruction: u16) -> (u8, u8) {
+    if instruction & 0b1111_000000_000000u16 == 0b1111_000000_000000u16 {
+        let first_operand = ((instruction & 0b0000_00_11111_00000u16) >> 5) as u8;
+        let second_operand = (instruction & 0b0000_00_00000_11111u16) as u8;
+
+        return (extended_operand_to_address(first_operand),
+                extended_operand_to_address(second_operand));
+    }
+
     let first_address = ((instruction & 0b0000_111111_000000u16) >> 6) as u8;
     let second_address = (instruction & 0b0000_000000_111111u16) as u8;
 
     return (first_address, second_address);
 }
 
+/// Converts a five bits operand (ttrrr) of an extended instruction to a
+/// six bits address (tt0rrr).
+fn extended_operand_to_address(operand: u8) -> u8 {
+    return ((operand & 0b11000u8) << 1) | (operand & 0b00111u8);
+}
+
 /// Extracts value that an address is pointing to, from a
 /// one-operand instruction.
 fn extract_one_operand_value(hardware: &Hardware, instruction: u16, supports_register_pc: bool)
-    -> Result<u16, String> {
+    -> Result<u16, ClockError> {
 
     let address = extract_one_operand_address(instruction);
 
@@ -161,10 +236,10 @@ fn extract_one_operand_value(hardware: &Hardware, instruction: u16, supports_reg
         Address::Register(register_number) =>
             hardware.registers[register_number as usize],
         Address::Memory(memory_address) =>
-            hardware.memory[memory_address as usize],
+            hardware.read_memory(memory_address),
         Address::RegisterPlusPC(jump_address) => {
             if !supports_register_pc {
-                return Err(format!("Unsupported address type. Instruction: {:b}", instruction));
+                return Err(ClockError::InvalidOperand(format!("Unsupported address type. Instruction: {:b}", instruction)));
             }
             jump_address
         },
@@ -179,7 +254,7 @@ fn extract_one_operand_value(hardware: &Hardware, instruction: u16, supports_reg
 /// @supports_register_pc: Whether the operation supports RegisterPlusPC address type.
 ///     If set to false, an Err will return in case of RegisterPlusPC address.
 fn extract_two_operand_value(hardware: &Hardware, instruction: u16, supports_register_pc: bool)
-    -> Result<(u16, u16), String> {
+    -> Result<(u16, u16), ClockError> {
 
     let (first_address, second_address) = extract_two_operand_address(instruction);
 
@@ -188,10 +263,10 @@ fn extract_two_operand_value(hardware: &Hardware, instruction: u16, supports_reg
         Address::Register(register_number) =>
             hardware.registers[register_number as usize],
         Address::Memory(memory_address) =>
-            hardware.memory[memory_address as usize],
+            hardware.read_memory(memory_address),
         Address::RegisterPlusPC(jump_address) => {
             if !supports_register_pc {
-                return Err(format!("Unsupported address type. Instruction: {:b}", instruction));
+                return Err(ClockError::InvalidOperand(format!("Unsupported address type. Instruction: {:b}", instruction)));
             }
             jump_address
         },
@@ -202,10 +277,10 @@ fn extract_two_operand_value(hardware: &Hardware, instruction: u16, supports_reg
         Address::Register(register_number) =>
             hardware.registers[register_number as usize],
         Address::Memory(memory_address) =>
-            hardware.memory[memory_address as usize],
+            hardware.read_memory(memory_address),
         Address::RegisterPlusPC(jump_address) => {
             if !supports_register_pc {
-                return Err(format!("Unsupported address type. Instruction: {:b}", instruction));
+                return Err(ClockError::InvalidOperand(format!("Unsupported address type. Instruction: {:b}", instruction)));
             }
             jump_address
         },
@@ -215,42 +290,59 @@ fn extract_two_operand_value(hardware: &Hardware, instruction: u16, supports_reg
 }
 
 /// It just increases program counter (skips this instruction).
-fn nop(hardware: &mut Hardware, _instruction: u16) -> Result<(), String> {
+fn nop(hardware: &mut Hardware, _instruction: u16) -> Result<(), ClockError> {
     hardware.program_counter += 1;
     return Ok(());
 }
 
 /// Do a sys call. Each sys call has its own conventions. See documentation.
-fn syscall(hardware: &mut Hardware, _instruction: u16) -> Result<(), String> {
+fn syscall(hardware: &mut Hardware, _instruction: u16) -> Result<(), ClockError> {
 
     let mut cpu_state = CPUState::new(&hardware.registers);
+    cpu_state.set_overflow_flag(hardware.overflow_flag);
+    cpu_state.set_underflow_flag(hardware.underflow_flag);
+    cpu_state.init_program_counter(hardware.program_counter);
+    cpu_state.set_call_stack_depth(hardware.call_stack.len() as u16);
 
     // Calling the sys call.
-    hardware.call_syscall(&mut cpu_state)?;
+    hardware.call_syscall(&mut cpu_state).map_err(ClockError::SyscallUnavailable)?;
 
-    // Setting changed registers in the hardware.
+    // Setting changed registers and flags in the hardware.
     for i in 0..hardware.registers.len() {
         hardware.registers[i] = cpu_state.get_register(i);
     }
+    hardware.overflow_flag = cpu_state.get_overflow_flag();
+    hardware.underflow_flag = cpu_state.get_underflow_flag();
 
-    hardware.program_counter += 1;
+    // If the sys call set the PC, execution continues from there.
+    if cpu_state.is_program_counter_set() {
+        hardware.program_counter = cpu_state.get_program_counter();
+    } else {
+        hardware.program_counter += 1;
+    }
 
     // Checking for errors.
     if cpu_state.get_error_flag() {
         hardware.error_flag = true;
-        return Err(String::from("Something went wrong when sys call is called."));
+        return Err(ClockError::SyscallFailed);
     }
 
     return Ok(());
 }
 
-fn return_subroutine(hardware: &mut Hardware, _instruction: u16) -> Result<(), String> {
+/// Stops the CPU. Program counter stays on this instruction.
+fn halt(hardware: &mut Hardware, _instruction: u16) -> Result<(), ClockError> {
+    hardware.halted = true;
+    return Ok(());
+}
+
+fn return_subroutine(hardware: &mut Hardware, _instruction: u16) -> Result<(), ClockError> {
 
    match hardware.call_stack.pop() {
         Some(pc) =>  hardware.program_counter = pc,
         None => {
             hardware.underflow_flag = true;
-            return Err(String::from("Call stack underflow"));
+            return Err(ClockError::CallStackUnderflow);
         }
     };
 
@@ -258,7 +350,7 @@ fn return_subroutine(hardware: &mut Hardware, _instruction: u16) -> Result<(), S
 }
 
 /// Jumps to the address inside the instruction.
-fn jump(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+fn jump(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
 
     hardware.program_counter =
         extract_one_operand_value(hardware, instruction, true)?;
@@ -266,11 +358,28 @@ fn jump(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
     return Ok(());
 }
 
-fn subroutine(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+/// Register that JUMP_LINK stores the return address in.
+const LINK_REGISTER: usize = 7;
+
+/// Stores the address of the next instruction in the link register, then jumps.
+/// Unlike SUBROUTINE, the call stack is not touched, so the program is free to
+/// choose how to return.
+fn jump_link(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
 
-    if hardware.call_stack.len() == Hardware::get_call_stack_size() {
+    // Target is extracted first, because the operand can be the link register itself.
+    let target = extract_one_operand_value(hardware, instruction, true)?;
+
+    hardware.registers[LINK_REGISTER] = hardware.program_counter + 1;
+    hardware.program_counter = target;
+
+    return Ok(());
+}
+
+fn subroutine(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+
+    if hardware.call_stack.len() == hardware.get_call_stack_size() {
         hardware.overflow_flag = true;
-        return Err(String::from("Call stack overflow."));
+        return Err(ClockError::CallStackOverflow);
     }
 
     // Storing return address.
@@ -284,7 +393,7 @@ fn subroutine(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
 }
 
 /// Skips next instruction if operand is pointing to an address with zero value.
-fn skip_if_zero(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+fn skip_if_zero(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
 
     let address_value = extract_one_operand_value(hardware, instruction, false)?;
 
@@ -297,17 +406,204 @@ fn skip_if_zero(hardware: &mut Hardware, instruction: u16) -> Result<(), String>
     return Ok(());
 }
 
+/// Applies the specified function on the value of a one-operand instruction,
+/// and stores the result back to the same address.
+/// RegisterPlusPC address type is not supported.
+/// Returns the stored result.
+///
+/// @operation_name: Used in the error message.
+fn update_one_operand_value(hardware: &mut Hardware, instruction: u16, operation_name: &str,
+                            function: fn(u16) -> u16) -> Result<u16, ClockError> {
+    let address = extract_one_operand_address(instruction);
+
+    let true_address = get_true_address(hardware, address)?;
+    let result = match true_address {
+        Address::Register(register_number) => {
+            let result = function(hardware.registers[register_number as usize]);
+            hardware.registers[register_number as usize] = result;
+            result
+        },
+        Address::Memory(memory_address) => {
+            let result = function(hardware.read_memory(memory_address));
+            hardware.write_memory(memory_address, result);
+            result
+        },
+        Address::RegisterPlusPC(_) =>
+            return Err(ClockError::InvalidOperand(format!("Invalid address type for {}. Instruction: {:b}",
+                               operation_name, instruction))),
+    };
+
+    hardware.program_counter += 1;
+
+    return Ok(result);
+}
+
+/// Applies the specified function on the values of a two-operand instruction,
+/// and stores the result back to the second address.
+/// RegisterPlusPC address type is not supported.
+///
+/// @operation_name: Used in the error message.
+fn update_two_operand_value(hardware: &mut Hardware, instruction: u16, operation_name: &str,
+                            function: fn(u16, u16) -> u16) -> Result<u16, ClockError> {
+    let (first_value, second_value) =
+        extract_two_operand_value(hardware, instruction, false)?;
+
+    let result = function(first_value, second_value);
+
+    let (_, second_address) = extract_two_operand_address(instruction);
+    match get_true_address(hardware, second_address)? {
+        Address::Register(register_number) => hardware.registers[register_number as usize] = result,
+        Address::Memory(memory_address) => hardware.write_memory(memory_address, result),
+        Address::RegisterPlusPC(_) =>
+            return Err(ClockError::InvalidOperand(format!("Invalid address type for {}. Instruction: {:b}",
+                               operation_name, instruction))),
+    }
+
+    hardware.program_counter += 1;
+
+    return Ok(result);
+}
+
+/// Counts the set bits of the operand, and stores the result back to the operand.
+fn popcount(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_one_operand_value(hardware, instruction, "POPCOUNT",
+                             |value| value.count_ones() as u16)?;
+
+    return Ok(());
+}
+
+/// Counts the leading zero bits of the operand, and stores the result back to the operand.
+/// Result of zero is 16.
+fn count_leading_zeros(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_one_operand_value(hardware, instruction, "CLZ",
+                             |value| value.leading_zeros() as u16)?;
+
+    return Ok(());
+}
+
+/// Counts the trailing zero bits of the operand, and stores the result back to the operand.
+/// Result of zero is 16.
+fn count_trailing_zeros(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_one_operand_value(hardware, instruction, "CTZ",
+                             |value| value.trailing_zeros() as u16)?;
+
+    return Ok(());
+}
+
+/// Increments the operand. Saturates at the maximum value.
+fn increment(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_one_operand_value(hardware, instruction, "INCREMENT",
+                             |value| value.saturating_add(1))?;
+
+    return Ok(());
+}
+
+/// Decrements the operand. Saturates at zero.
+fn decrement(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_one_operand_value(hardware, instruction, "DECREMENT",
+                             |value| value.saturating_sub(1))?;
+
+    return Ok(());
+}
+
+/// Pushes value of the operand to the data stack.
+fn push(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+
+    if hardware.data_stack.len() == Hardware::get_data_stack_size() {
+        hardware.overflow_flag = true;
+        return Err(ClockError::DataStackOverflow);
+    }
+
+    let value = extract_one_operand_value(hardware, instruction, false)?;
+    hardware.data_stack.push(value);
+
+    hardware.program_counter += 1;
+
+    return Ok(());
+}
+
+/// Pops a value from the data stack, and stores it in the operand.
+fn pop(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+
+    let value = match hardware.data_stack.last() {
+        Some(v) => *v,
+        None => {
+            hardware.underflow_flag = true;
+            return Err(ClockError::DataStackUnderflow);
+        },
+    };
+
+    let address = extract_one_operand_address(instruction);
+    match get_true_address(hardware, address)? {
+        Address::Register(register_number) => hardware.registers[register_number as usize] = value,
+        Address::Memory(memory_address) => hardware.write_memory(memory_address, value),
+        Address::RegisterPlusPC(_) =>
+            return Err(ClockError::InvalidOperand(format!("Invalid address type for POP. Instruction: {:b}",
+                               instruction))),
+    }
+
+    hardware.data_stack.pop();
+    hardware.program_counter += 1;
+
+    return Ok(());
+}
+
+/// Decrements the operand (saturates at zero), then skips the next
+/// instruction if the result is zero.
+fn dec_skip_if_zero(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+
+    let result = update_one_operand_value(hardware, instruction, "DEC_SKIP_IF_ZERO",
+                                          |value| value.saturating_sub(1))?;
+
+    if result == 0 {
+        // Program counter is already increased by one.
+        hardware.program_counter += 1;
+    }
+
+    return Ok(());
+}
+
 /// Copy value of an address to another.
-fn copy(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+fn copy(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    let (source_address, destination_address) = extract_two_operand_address(instruction);
+
+    let source_true_address = get_operand_address(hardware, source_address, "source", instruction)?;
+    let source_value = match source_true_address {
+        Address::Register(register_number) => hardware.registers[register_number as usize],
+        Address::Memory(memory_address) => hardware.read_memory(memory_address),
+        Address::RegisterPlusPC(_) =>
+            return Err(ClockError::InvalidOperand(format!("Invalid source address type for COPY. Instruction: {:b}",
+                               instruction))),
+    };
+
+    let destination_true_address = get_operand_address(hardware, destination_address, "destination", instruction)?;
+    match destination_true_address {
+        Address::Register(register_number) =>
+            hardware.registers[register_number as usize] = source_value,
+        Address::Memory(memory_address) =>
+            hardware.write_memory(memory_address, source_value),
+        Address::RegisterPlusPC(_) =>
+            return Err(ClockError::InvalidOperand(format!("Invalid destination address type for COPY. Instruction: {:b}",
+                               instruction))),
+    }
+
+    hardware.program_counter += 1;
+
+    return Ok(());
+}
+
+/// Copies value of the first operand to the second one, then sets the first
+/// operand to zero.
+fn take(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
     let (source_address, destination_address) = extract_two_operand_address(instruction);
 
     let source_true_address = get_true_address(hardware, source_address)?;
     let source_value = match source_true_address {
         Address::Register(register_number) => hardware.registers[register_number as usize],
-        Address::Memory(memory_address) => hardware.memory[memory_address as usize],
+        Address::Memory(memory_address) => hardware.read_memory(memory_address),
         Address::RegisterPlusPC(_) =>
-            return Err(format!("Invalid source address type for COPY. Instruction: {:b}",
-                               instruction)),
+            return Err(ClockError::InvalidOperand(format!("Invalid source address type for TAKE. Instruction: {:b}",
+                               instruction))),
     };
 
     let destination_true_address = get_true_address(hardware, destination_address)?;
@@ -315,10 +611,17 @@ fn copy(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
         Address::Register(register_number) =>
             hardware.registers[register_number as usize] = source_value,
         Address::Memory(memory_address) =>
-            hardware.memory[memory_address as usize] = source_value,
+            hardware.write_memory(memory_address, source_value),
         Address::RegisterPlusPC(_) =>
-            return Err(format!("Invalid destination address type for COPY. Instruction: {:b}",
-                               instruction)),
+            return Err(ClockError::InvalidOperand(format!("Invalid destination address type for TAKE. Instruction: {:b}",
+                               instruction))),
+    }
+
+    // Source is cleared after the copy, so if both operands are the same, it becomes zero.
+    match source_true_address {
+        Address::Register(register_number) => hardware.registers[register_number as usize] = 0,
+        Address::Memory(memory_address) => hardware.write_memory(memory_address, 0),
+        Address::RegisterPlusPC(_) => (),
     }
 
     hardware.program_counter += 1;
@@ -327,36 +630,39 @@ fn copy(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
 }
 
 /// Adds two values.
-fn add(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+fn add(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
     let (first_address, second_address) = extract_two_operand_address(instruction);
 
-    let true_first_address = get_true_address(hardware, first_address)?;
+    let true_first_address = get_operand_address(hardware, first_address, "source", instruction)?;
     let first_value = match true_first_address {
         Address::Register(register_number) => hardware.registers[register_number as usize],
-        Address::Memory(memory_address) => hardware.memory[memory_address as usize],
+        Address::Memory(memory_address) => hardware.read_memory(memory_address),
         Address::RegisterPlusPC(_) =>
-            return Err(format!("Invalid source address type for ADD. Instruction: {:b}",
-                               instruction)),
+            return Err(ClockError::InvalidOperand(format!("Invalid source address type for ADD. Instruction: {:b}",
+                               instruction))),
     };
 
-    let true_second_address = get_true_address(hardware, second_address)?;
+    let true_second_address = get_operand_address(hardware, second_address, "destination", instruction)?;
     let second_value = match true_second_address {
         Address::Register(register_number) => hardware.registers[register_number as usize],
-        Address::Memory(memory_address) => hardware.memory[memory_address as usize],
+        Address::Memory(memory_address) => hardware.read_memory(memory_address),
         Address::RegisterPlusPC(_) =>
-            return Err(format!("Invalid source address type for ADD. Instruction: {:b}",
-                               instruction)),
+            return Err(ClockError::InvalidOperand(format!("Invalid source address type for ADD. Instruction: {:b}",
+                               instruction))),
     };
 
-    let result = first_value.saturating_add(second_value);
+    // Result saturates at the maximum value, and the overflow flag is set.
+    let (result, is_overflowed) = first_value.overflowing_add(second_value);
+    let result = if is_overflowed { u16::MAX } else { result };
+    hardware.overflow_flag = is_overflowed;
 
     // Storing the result back to the second address.
     match true_second_address {
         Address::Register(register_number) => hardware.registers[register_number as usize] = result,
-        Address::Memory(memory_address) => hardware.memory[memory_address as usize] = result,
+        Address::Memory(memory_address) => hardware.write_memory(memory_address, result),
         Address::RegisterPlusPC(_) =>
-            return Err(format!("Invalid source address type for ADD. Instruction: {:b}",
-                               instruction)),
+            return Err(ClockError::InvalidOperand(format!("Invalid source address type for ADD. Instruction: {:b}",
+                               instruction))),
     }
 
     hardware.program_counter += 1;
@@ -364,37 +670,49 @@ fn add(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
     return Ok(());
 }
 
+/// Adds two values, and stores the result in the second operand.
+/// Unlike ADD, result wraps around on overflow, and flags are untouched.
+fn add_wrapping(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_two_operand_value(hardware, instruction, "ADD_WRAPPING",
+                             |first, second| first.wrapping_add(second))?;
+
+    return Ok(());
+}
+
 /// Subtracts two values.
-fn subtract(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+fn subtract(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
     let (first_address, second_address) = extract_two_operand_address(instruction);
 
-    let true_first_address = get_true_address(hardware, first_address)?;
+    let true_first_address = get_operand_address(hardware, first_address, "source", instruction)?;
     let first_value = match true_first_address {
         Address::Register(register_number) => hardware.registers[register_number as usize],
-        Address::Memory(memory_address) => hardware.memory[memory_address as usize],
+        Address::Memory(memory_address) => hardware.read_memory(memory_address),
         Address::RegisterPlusPC(_) =>
-            return Err(format!("Invalid source address type for SUBTRACT. Instruction: {:b}",
-                               instruction)),
+            return Err(ClockError::InvalidOperand(format!("Invalid source address type for SUBTRACT. Instruction: {:b}",
+                               instruction))),
     };
 
-    let true_second_address = get_true_address(hardware, second_address)?;
+    let true_second_address = get_operand_address(hardware, second_address, "destination", instruction)?;
     let second_value = match true_second_address {
         Address::Register(register_number) => hardware.registers[register_number as usize],
-        Address::Memory(memory_address) => hardware.memory[memory_address as usize],
+        Address::Memory(memory_address) => hardware.read_memory(memory_address),
         Address::RegisterPlusPC(_) =>
-            return Err(format!("Invalid source address type for SUBTRACT. Instruction: {:b}",
-                               instruction)),
+            return Err(ClockError::InvalidOperand(format!("Invalid source address type for SUBTRACT. Instruction: {:b}",
+                               instruction))),
     };
 
-    let result = first_value.saturating_sub(second_value);
+    // Result saturates at zero, and the overflow flag is set.
+    let (result, is_overflowed) = first_value.overflowing_sub(second_value);
+    let result = if is_overflowed { 0 } else { result };
+    hardware.overflow_flag = is_overflowed;
 
     // Storing the result back to the second address.
     match true_second_address {
         Address::Register(register_number) => hardware.registers[register_number as usize] = result,
-        Address::Memory(memory_address) => hardware.memory[memory_address as usize] = result,
+        Address::Memory(memory_address) => hardware.write_memory(memory_address, result),
         Address::RegisterPlusPC(_) =>
-            return Err(format!("Invalid source address type for SUBTRACT. Instruction: {:b}",
-                               instruction)),
+            return Err(ClockError::InvalidOperand(format!("Invalid source address type for SUBTRACT. Instruction: {:b}",
+                               instruction))),
     }
 
     hardware.program_counter += 1;
@@ -403,7 +721,7 @@ fn subtract(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
 }
 
 /// Skips the next instruction if value of two operands are equal.
-fn skip_if_equal(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+fn skip_if_equal(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
 
     let (first_value, second_value) =
         extract_two_operand_value(hardware, instruction, false)?;
@@ -418,7 +736,7 @@ fn skip_if_equal(hardware: &mut Hardware, instruction: u16) -> Result<(), String
 }
 
 /// Skips the next instruction if value of first operand is greater than the second one.
-fn skip_if_greater(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+fn skip_if_greater(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
     let (first_value, second_value) =
         extract_two_operand_value(hardware, instruction, false)?;
 
@@ -431,21 +749,267 @@ fn skip_if_greater(hardware: &mut Hardware, instruction: u16) -> Result<(), Stri
     return Ok(());
 }
 
+/// Compares the value of the first operand with the second one, and stores the
+/// result in the second operand: zero if they're equal, one if the first is
+/// greater, and two if the first is less.
+fn compare(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_two_operand_value(hardware, instruction, "COMPARE",
+                             |first, second| {
+                                 if first == second {
+                                     0
+                                 } else if first > second {
+                                     1
+                                 } else {
+                                     2
+                                 }
+                             })?;
+
+    return Ok(());
+}
+
+/// Skips the next instruction if value of first operand is greater than the second one.
+/// Values are compared as signed (two's complement) numbers.
+fn skip_if_greater_signed(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    let (first_value, second_value) =
+        extract_two_operand_value(hardware, instruction, false)?;
+
+    if first_value as i16 > second_value as i16 {
+        hardware.program_counter += 2;
+    } else {
+        hardware.program_counter += 1;
+    }
+
+    return Ok(());
+}
+
 /// Sets a constant to a register.
-fn set(hardware: &mut Hardware, instruction: u16) -> Result<(), String> {
+/// Register number is three bits and the constant is nine bits, so the register
+/// is always valid and the constant is at most 511, whatever the binary is.
+fn set(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
 
     let register_number = (0b0000_111_000000000u16 & instruction) >> 9;
     let constant = 0b0000_000_111111111u16 & instruction;
 
+    // Masks above guarantee these, but a wrong constant must never reach the registers.
+    if register_number > 7 || constant > 511 {
+        return Err(ClockError::InvalidOperand(format!(
+            "Invalid SET: register [{}] constant [{}]. Instruction: {:b}",
+            register_number, constant, instruction)));
+    }
+
     hardware.registers[register_number as usize] = constant;
     hardware.program_counter += 1;
 
     return Ok(());
 }
 
+/// Shifts value of the first operand to the left, by the value of the second operand.
+/// Stores the result in the second operand.
+fn shift_left(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_two_operand_value(hardware, instruction, "SHIFT_LEFT",
+                             |value, amount| value.wrapping_shl(amount as u32))?;
+
+    return Ok(());
+}
+
+/// Shifts value of the first operand to the right, by the value of the second operand.
+/// Stores the result in the second operand.
+fn shift_right(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    update_two_operand_value(hardware, instruction, "SHIFT_RIGHT",
+                             |value, amount| value.wrapping_shr(amount as u32))?;
+
+    return Ok(());
+}
+
+/// Loads the word after the instruction into a register. It's a two-word instruction,
+/// so the program counter is advanced by two.
+fn load_immediate(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+
+    let register_number = (0b0000_111_000000000u16 & instruction) >> 9;
+
+    let constant_address = hardware.program_counter as usize + 1;
+    if constant_address >= hardware.memory.len() {
+        return Err(ClockError::InvalidOperand(String::from("Constant of LOAD_IMMEDIATE is out of memory.")));
+    }
+
+    hardware.registers[register_number as usize] = hardware.read_memory(constant_address as u16);
+    hardware.program_counter += 2;
+
+    return Ok(());
+}
+
+/// Exchanges values of the two operands.
+/// RegisterPlusPC address type is not supported.
+fn swap(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    let (first_value, second_value) =
+        extract_two_operand_value(hardware, instruction, false)?;
+
+    let (first_address, second_address) = extract_two_operand_address(instruction);
+    let true_first_address = get_true_address(hardware, first_address)?;
+    let true_second_address = get_true_address(hardware, second_address)?;
+
+    match true_first_address {
+        Address::Register(register_number) => hardware.registers[register_number as usize] = second_value,
+        Address::Memory(memory_address) => hardware.write_memory(memory_address, second_value),
+        Address::RegisterPlusPC(_) =>
+            return Err(ClockError::InvalidOperand(format!("Invalid address type for SWAP. Instruction: {:b}",
+                               instruction))),
+    }
+
+    match true_second_address {
+        Address::Register(register_number) => hardware.registers[register_number as usize] = first_value,
+        Address::Memory(memory_address) => hardware.write_memory(memory_address, first_value),
+        Address::RegisterPlusPC(_) =>
+            return Err(ClockError::InvalidOperand(format!("Invalid address type for SWAP. Instruction: {:b}",
+                               instruction))),
+    }
+
+    hardware.program_counter += 1;
+
+    return Ok(());
+}
+
+/// Writes a value into a range of the memory. Unlike other extended operations,
+/// operands are three registers: 1111 10 0 sss lll vvv
+/// where sss is the start address, lll is the length, and vvv is the value.
+fn fill(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+
+    if instruction & 0b0000_00_1_000_000_000u16 != 0 {
+        return Err(ClockError::InvalidOperand(format!("Unused bit of FILL is set. Instruction: {:b}",
+                                                      instruction)));
+    }
+
+    let start = hardware.registers[((instruction & 0b0000_00_0_111_000_000u16) >> 6) as usize];
+    let length = hardware.registers[((instruction & 0b0000_00_0_000_111_000u16) >> 3) as usize];
+    let value = hardware.registers[(instruction & 0b0000_00_0_000_000_111u16) as usize];
+
+    if let Err(error) = hardware.fill_memory(start, length, value) {
+        return Err(ClockError::InvalidOperand(format!("{} Start: [{}] Length: [{}]",
+                                                      error, start, length)));
+    }
+
+    hardware.program_counter += 1;
+
+    return Ok(());
+}
+
+/// Extracts register number and the bit field from a bit field instruction.
+/// Bit field instructions are: 4 bits operation, 3 bits register, 4 bits
+/// lowest bit of the field, and 5 bits width of the field.
+///
+/// Returns (register number, lowest bit, mask of the field in its place).
+fn extract_bit_field(instruction: u16) -> Result<(usize, u32, u16), ClockError> {
+    let register_number = ((0b0000_111_0000_00000u16 & instruction) >> 9) as usize;
+    let lowest_bit = ((0b0000_000_1111_00000u16 & instruction) >> 5) as u32;
+    let width = (0b0000_000_0000_11111u16 & instruction) as u32;
+
+    if width == 0 || lowest_bit + width > 16 {
+        return Err(ClockError::InvalidOperand(format!("Bit field is out of range: lowest bit [{}] width [{}]",
+                           lowest_bit, width)));
+    }
+
+    // Width can be 16, so shifting may overflow.
+    let mask = 1u16.checked_shl(width).unwrap_or(0).wrapping_sub(1);
+
+    return Ok((register_number, lowest_bit, mask << lowest_bit));
+}
+
+/// Extracts a bit field of a register, and stores it back to the register.
+fn bit_field_extract(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    let (register_number, lowest_bit, mask) = extract_bit_field(instruction)?;
+
+    hardware.registers[register_number] =
+        (hardware.registers[register_number] & mask) >> lowest_bit;
+    hardware.program_counter += 1;
+
+    return Ok(());
+}
+
+/// Inserts the lowest bits of register zero into a bit field of a register.
+fn bit_field_insert(hardware: &mut Hardware, instruction: u16) -> Result<(), ClockError> {
+    let (register_number, lowest_bit, mask) = extract_bit_field(instruction)?;
+
+    let field = (hardware.registers[0] << lowest_bit) & mask;
+    hardware.registers[register_number] = (hardware.registers[register_number] & !mask) | field;
+    hardware.program_counter += 1;
+
+    return Ok(());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    /// Codes of all the defined operations.
+    const DEFINED_CODES: [u16; 33] = [
+        // No operand operations
+        0b0000000000_000000u16, 0b0000000000_000001u16, 0b0000000000_000010u16, 0b0000000000_000011u16,
+        // Single operand operations
+        0b0000_000001_000000u16, 0b0000_000010_000000u16, 0b0000_000011_000000u16, 0b0000_000100_000000u16,
+        0b0000_000101_000000u16, 0b0000_000110_000000u16, 0b0000_000111_000000u16, 0b0000_001000_000000u16,
+        0b0000_001001_000000u16, 0b0000_001010_000000u16, 0b0000_001011_000000u16, 0b0000_001100_000000u16,
+        // Double operand operations
+        0b0001_000000000000u16, 0b0010_000000000000u16, 0b0011_000000000000u16, 0b0100_000000000000u16,
+        0b0101_000000000000u16, 0b0110_000000000000u16, 0b0111_000000000000u16, 0b1000_000000000000u16,
+        0b1001_000000000000u16, 0b1010_000000000000u16, 0b1011_000000000000u16, 0b1100_000000000000u16,
+        0b1101_000000000000u16, 0b1110_000000000000u16,
+        // Extended double operand operations
+        0b1111_00_0000000000u16, 0b1111_01_0000000000u16, 0b1111_10_0000000000u16,
+    ];
+
+    /// Decodes every possible instruction. Each defined operation should be
+    /// dispatched, unused codes of every operation type should be rejected, and
+    /// no two operations should share a code.
+    #[test]
+    fn operation_codes() {
+        let operations = Operations::new();
+
+        // Operation code => address of its function.
+        let mut dispatched: HashMap<u16, usize> = HashMap::new();
+
+        for instruction in 0..=u16::max_value() {
+            if let Ok(function) = operations.get_function(instruction) {
+                let code = OperationCode::new(instruction).code();
+                let function_address = function as usize;
+
+                // Operands don't change the operation.
+                assert_eq!(*dispatched.entry(code).or_insert(function_address), function_address);
+            }
+        }
+
+        let mut codes: Vec<u16> = dispatched.keys().cloned().collect();
+        codes.sort();
+        let mut expected_codes = DEFINED_CODES.to_vec();
+        expected_codes.sort();
+        expected_codes.dedup();
+        assert_eq!(expected_codes.len(), DEFINED_CODES.len());
+        assert_eq!(codes, expected_codes);
+
+        let functions: HashSet<usize> = dispatched.values().cloned().collect();
+        assert_eq!(functions.len(), DEFINED_CODES.len());
+    }
+
+    #[test]
+    fn operations_new() {
+        let operations = Operations::new();
+
+        assert_eq!(operations.get_function(0b0000000000_000000u16).is_ok(), true);
+        assert_eq!(operations.get_function(0b0101_000011_010110u16).is_ok(), true);
+        assert_eq!(operations.get_function(0b1111_110011_010110u16).is_err(), true);
+
+        assert_eq!(operations.get_cost(0b0000000000_000000u16), 1);
+        assert_eq!(operations.get_cost(0b0000000000_000001u16), 5);
+        assert_eq!(operations.get_cost(0b1011_011_000000000u16), 2);
+
+        // Unused codes of each operation type.
+        assert_eq!(operations.get_function(0b0000000000_111111u16).is_err(), true);
+        assert_eq!(operations.get_function(0b0000_111111_000000u16).is_err(), true);
+        assert_eq!(operations.get_function(0b0000_001101_000000u16).is_err(), true);
+        assert_eq!(operations.get_function(0b1111_000000_000000u16).is_ok(), true);
+        assert_eq!(operations.get_function(0b1111_100000_000000u16).is_ok(), true);
+        assert_eq!(operations.get_function(0b1111_110000_000000u16).is_err(), true);
+    }
 
     /// Tests address type one.
     #[test]
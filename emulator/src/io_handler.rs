@@ -0,0 +1,27 @@
+// This file is part of Sillyverse.
+// Copyright (C) 2017-2020, Aidin Gharibnavaz <aidin@aidinhut.com>
+//
+// Sillyverse is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Sillyverse is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
+
+/// Structure that is responsible for handling a memory-mapped I/O region
+/// should implement this trait.
+pub trait IoHandler {
+
+    /// Will be called whenever the program reads from an address of the region.
+    /// Returned value is what the program reads.
+    fn read(&mut self, address: u16) -> u16;
+
+    /// Will be called whenever the program writes to an address of the region.
+    fn write(&mut self, address: u16, value: u16);
+}
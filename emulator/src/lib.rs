@@ -19,6 +19,10 @@
 mod hardware;
 mod cpu_state;
 mod sys_callback;
+mod run_summary;
+mod io_handler;
+mod clock_error;
+pub mod disasm;
 
 use std::rc::Weak;
 use std::sync::Mutex;
@@ -29,7 +33,11 @@ use std::io::Read;
 
 // Importing public API types.
 pub use cpu_state::CPUState;
-pub use sys_callback::SysCallback;
+pub use sys_callback::{SysCallback, InstructionTrace};
+pub use hardware::{EndOfMemoryBehavior, HardwareSnapshot};
+pub use run_summary::{RunSummary, TerminationReason};
+pub use io_handler::IoHandler;
+pub use clock_error::ClockError;
 
 
 pub struct Emulator {
@@ -47,6 +55,16 @@ impl Emulator {
         }
     }
 
+    /// Creates an instance of the Emulator struct with a custom call stack size.
+    ///
+    /// @memory_size: Size of the hardware memory. Max is 65536.
+    /// @call_stack_size: Maximum number of nested subroutine calls.
+    pub fn new_with_limits(memory_size: u16, call_stack_size: usize) -> Emulator {
+        Emulator {
+            hardware: hardware::Hardware::new_with_limits(memory_size, call_stack_size),
+        }
+    }
+
     /// Loads the specified data into memory.
     /// Returns error if data won't fit into memory.
     ///
@@ -56,7 +74,57 @@ impl Emulator {
         return self.hardware.load(data, start);
     }
 
+    /// Loads several segments into the memory in one call.
+    /// All segments are checked before anything is written, so if any of
+    /// them is invalid, memory is left untouched.
+    ///
+    /// @segments: List of (start, data) pairs. Segments can't overlap.
+    pub fn load_segments(&mut self, segments: &[(u16, Vec<u16>)]) -> Result<(), &'static str> {
+        let memory_size = self.hardware.get_memory().len();
+
+        // Range of each segment: (start, end). Empty segments don't occupy anything.
+        let mut ranges: Vec<(usize, usize)> = segments.iter()
+            .filter(|&&(_, ref data)| !data.is_empty())
+            .map(|&(start, ref data)| (start as usize, start as usize + data.len()))
+            .collect();
+
+        if ranges.iter().any(|&(_, end)| end > memory_size) {
+            return Err("Out of memory: Segment won't fit in memory starting from specified address.");
+        }
+
+        ranges.sort();
+        if ranges.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+            return Err("Segments are overlapping.");
+        }
+
+        for &(start, ref data) in segments {
+            self.hardware.load(data, start)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Loads a program into memory, and sets the program counter to its start,
+    /// so the execution begins from the first instruction of the program.
+    ///
+    /// @data: Code of the program.
+    /// @start: Memory address to load the program into.
+    pub fn load_program(&mut self, data: &Vec<u16>, start: u16) -> Result<(), &'static str> {
+        self.load(data, start)?;
+        return self.set_program_counter(start);
+    }
+
+    /// Loads big-endian two-byte instructions from the file into the memory.
     pub fn load_from_file(&mut self, file_path: &String, start: u16) -> Result<(), &'static str> {
+        return self.load_from_file_endian(file_path, start, false);
+    }
+
+    /// Loads two-byte instructions from the file into the memory.
+    ///
+    /// @start: Memory address to start loading from.
+    /// @little_endian: If true, the first byte of each word is its low byte.
+    pub fn load_from_file_endian(&mut self, file_path: &String, start: u16, little_endian: bool)
+                                 -> Result<(), &'static str> {
         let file = match File::open(&file_path) {
             Ok(f) => f,
             Err(ioerror) => {
@@ -66,45 +134,279 @@ impl Emulator {
         };
 
         let mut reader = BufReader::new(&file);
-        let mut instruction: [u8; 2] = [0; 2];
-        let mut data: Vec<u16> = Vec::new();
-
-        loop {
-            let read_size = match reader.read(&mut instruction) {
-                Ok(size) => size,
-                Err(ioerror) => {
-                    eprintln!("Error reading file [{}]: {}", file_path, ioerror);
-                    return Err("Could not read from file. See stderr for the error.");
-                },
-            };
-
-            if read_size == 0 {
-                // End of file.
-                break;
-            }
+        let mut bytes: Vec<u8> = Vec::new();
 
-            if read_size == 1 {
-                return Err("File should be multiply of two-bytes.");
-            }
+        if let Err(ioerror) = reader.read_to_end(&mut bytes) {
+            eprintln!("Error reading file [{}]: {}", file_path, ioerror);
+            return Err("Could not read from file. See stderr for the error.");
+        }
+
+        return self.load_from_bytes_endian(&bytes, start, little_endian);
+    }
 
-            data.push(((instruction[0] as u16) << 8) | (instruction[1] as u16));
+    /// Loads a program from the file, and sets the program counter to its start,
+    /// so the execution begins from the first instruction of the program.
+    ///
+    /// @start: Memory address to load the program into.
+    pub fn load_program_from_file(&mut self, file_path: &String, start: u16) -> Result<(), &'static str> {
+        self.load_from_file(file_path, start)?;
+        return self.set_program_counter(start);
+    }
+
+    /// Loads big-endian two-byte instructions into the memory.
+    ///
+    /// @bytes: Binary code. Its size should be a multiply of two.
+    /// @start: Memory address to start loading from.
+    pub fn load_from_bytes(&mut self, bytes: &[u8], start: u16) -> Result<(), &'static str> {
+        return self.load_from_bytes_endian(bytes, start, false);
+    }
+
+    /// Loads two-byte instructions into the memory.
+    ///
+    /// @bytes: Binary code. Its size should be a multiply of two.
+    /// @start: Memory address to start loading from.
+    /// @little_endian: If true, the first byte of each word is its low byte.
+    pub fn load_from_bytes_endian(&mut self, bytes: &[u8], start: u16, little_endian: bool)
+                                  -> Result<(), &'static str> {
+        if bytes.len() % 2 != 0 {
+            return Err("File should be multiply of two-bytes.");
         }
 
+        let data: Vec<u16> = bytes.chunks(2)
+            .map(|instruction| {
+                if little_endian {
+                    return ((instruction[1] as u16) << 8) | (instruction[0] as u16);
+                }
+                return ((instruction[0] as u16) << 8) | (instruction[1] as u16);
+            })
+            .collect();
+
         return self.load(&data, start);
     }
 
     /// Executes a clock of CPU.
     /// Returns error only if something really goes wrong
     /// (hardware state is corrupted).
-    pub fn clock(&mut self) -> Result<(), String> {
+    pub fn clock(&mut self) -> Result<(), ClockError> {
         return self.hardware.clock();
     }
 
+    /// Runs a tiny known program on the emulator and checks its result.
+    /// Embedders can use it to ensure the emulator is wired up correctly.
+    ///
+    /// It should be called before running a program: CPU will be reset
+    /// afterward, but memory is left as it was.
+    pub fn self_test(&mut self) -> Result<(), String> {
+        return self.hardware.self_test();
+    }
+
+    /// Resets the CPU to its initial state: registers, program counter, flags and
+    /// call stack. Memory is untouched, so the loaded program can be run again.
+    pub fn reset(&mut self) {
+        self.hardware.reset();
+    }
+
+    /// Captures the full state of the emulator: memory, registers, program
+    /// counter, flags and the call stack.
+    pub fn snapshot(&self) -> HardwareSnapshot {
+        return self.hardware.snapshot();
+    }
+
+    /// Restores a state that is captured by `snapshot`. Current state, memory
+    /// included, is fully overwritten.
+    pub fn restore(&mut self, snapshot: &HardwareSnapshot) {
+        self.hardware.restore(snapshot);
+    }
+
+    /// Whether the CPU is halted. A halted CPU does nothing on clock.
+    pub fn is_halted(&self) -> bool {
+        return self.hardware.is_halted();
+    }
+
+    /// Returns the program counter, i.e. address of the next instruction.
+    /// After a failed clock, it points to the instruction that failed.
+    pub fn get_program_counter(&self) -> u16 {
+        return self.hardware.get_program_counter();
+    }
+
+    /// Sets the address of the next instruction to execute.
+    /// Returns error if the address is beyond the memory.
+    pub fn set_program_counter(&mut self, program_counter: u16) -> Result<(), &'static str> {
+        if program_counter >= self.hardware.get_memory_size() {
+            return Err("Program counter is out of memory.");
+        }
+
+        self.hardware.set_program_counter(program_counter);
+
+        return Ok(());
+    }
+
+    /// Recovers the CPU after a failed clock, so it can be clocked again.
+    /// Memory, registers, and other flags are untouched. Program counter still
+    /// points to the instruction that failed, unless a new one is provided.
+    ///
+    /// Returns error if the provided program counter is out of memory. Error
+    /// won't be cleared in this case.
+    ///
+    /// @program_counter: Address to continue the execution from.
+    pub fn clear_error(&mut self, program_counter: Option<u16>) -> Result<(), &'static str> {
+        if let Some(address) = program_counter {
+            self.set_program_counter(address)?;
+        }
+
+        self.hardware.clear_error_flag();
+
+        return Ok(());
+    }
+
+    /// Returns number of the clocks that executed an instruction successfully.
+    /// Clocks of a halted CPU, and failed clocks are not counted.
+    pub fn clock_count(&self) -> u64 {
+        return self.hardware.get_clock_count();
+    }
+
+    /// Returns cost of the last successful clock. Expensive instructions, like
+    /// SYSCALL, cost more than one. Clocks of a halted CPU cost one.
+    pub fn last_instruction_cost(&self) -> u16 {
+        return self.hardware.get_last_instruction_cost();
+    }
+
+    /// Returns value of the specified register.
+    /// Returns error if the index is not a valid register (0 to 7).
+    pub fn get_register(&self, index: usize) -> Result<u16, &'static str> {
+        let registers = self.hardware.get_registers();
+
+        if index >= registers.len() {
+            return Err("Invalid register index.");
+        }
+
+        return Ok(registers[index]);
+    }
+
+    /// Returns values of all the registers.
+    pub fn get_registers(&self) -> [u16; 8] {
+        return self.hardware.get_registers();
+    }
+
+    /// Returns value of the specified memory address. Addresses in a memory-mapped
+    /// I/O region are read from its handler.
+    /// Returns error if the address is out of the memory.
+    pub fn read_memory(&self, address: u16) -> Result<u16, &'static str> {
+        if address >= self.hardware.get_memory_size() {
+            return Err("Address is out of the memory.");
+        }
+
+        return Ok(self.hardware.read_memory(address));
+    }
+
+    /// Writes the value into the specified memory address. Addresses in a
+    /// memory-mapped I/O region are written to its handler.
+    /// Returns error if the address is out of the memory.
+    pub fn write_memory(&mut self, address: u16, value: u16) -> Result<(), &'static str> {
+        if address >= self.hardware.get_memory_size() {
+            return Err("Address is out of the memory.");
+        }
+
+        self.hardware.write_memory(address, value);
+
+        return Ok(());
+    }
+
+    /// Writes the value into "length" words of the memory, starting from "start".
+    /// Returns error if the range is out of the memory. Memory won't be touched
+    /// if error return.
+    pub fn fill_memory(&mut self, start: u16, length: u16, value: u16) -> Result<(), &'static str> {
+        return self.hardware.fill_memory(start, length, value);
+    }
+
+    /// Sets what should happen when program counter goes beyond the end of the
+    /// memory, e.g. when a program doesn't end with a HALT. Default is
+    /// `EndOfMemoryBehavior::Error`.
+    pub fn set_end_of_memory_behavior(&mut self, behavior: EndOfMemoryBehavior) {
+        self.hardware.set_end_of_memory_behavior(behavior);
+    }
+
+    /// Clocks the CPU until it halts, an error occurs, or "max_cycles" clocks
+    /// are executed. Returns a summary of the run.
+    ///
+    /// @max_cycles: Maximum number of clocks to execute.
+    pub fn run_to_completion(&mut self, max_cycles: u64) -> RunSummary {
+        let mut cycles: u64 = 0;
+
+        let termination_reason = loop {
+            if self.hardware.is_halted() {
+                break TerminationReason::Halted;
+            }
+
+            if cycles >= max_cycles {
+                break TerminationReason::BudgetExhausted;
+            }
+
+            if let Err(error) = self.hardware.clock() {
+                break TerminationReason::Error(error);
+            }
+
+            // The clock that halts the CPU doesn't execute anything.
+            if !self.hardware.is_halted() {
+                cycles += 1;
+            }
+        };
+
+        return RunSummary::new(cycles, termination_reason, self.hardware.get_registers());
+    }
+
+    /// Clocks the CPU up to "max_clocks" times. Stops early if the CPU halts.
+    /// Returns number of the executed clocks, or the error of the clock that failed.
+    ///
+    /// @max_clocks: Maximum number of clocks to execute.
+    pub fn run(&mut self, max_clocks: u64) -> Result<u64, ClockError> {
+        let mut clocks: u64 = 0;
+
+        while clocks < max_clocks && !self.hardware.is_halted() {
+            self.hardware.clock()?;
+
+            // The clock that halts the CPU doesn't execute anything.
+            if !self.hardware.is_halted() {
+                clocks += 1;
+            }
+        }
+
+        return Ok(clocks);
+    }
+
     /// Registers a callback function that is responsible for handling sys calls.
     pub fn register_sys_callback(&mut self, callback: Weak<Mutex<dyn SysCallback>>) {
         self.hardware.register_sys_callback(callback);
     }
 
+    /// Registers a callback that is owned by the emulator itself.
+    ///
+    /// Unlike `register_sys_callback`, the callback doesn't need to be shared, so
+    /// there's no need to upgrade a weak reference or lock a mutex on each sys call.
+    /// Suitable for single-threaded hosts.
+    pub fn register_sys_callback_boxed(&mut self, callback: Box<dyn SysCallback>) {
+        self.hardware.register_sys_callback_boxed(callback);
+    }
+
+    /// Registers a callback that will be called before executing each instruction.
+    pub fn register_trace(&mut self, trace: Weak<Mutex<dyn InstructionTrace>>) {
+        self.hardware.register_trace(trace);
+    }
+
+    /// Maps a memory region to a host handler. When the program reads or writes
+    /// an address in this region, the handler is called instead of touching
+    /// the memory. Executing instructions from a mapped region is an error.
+    ///
+    /// Returns error if the region is empty, is out of the memory, or overlaps
+    /// with a region that is already mapped.
+    ///
+    /// @start: First address of the region.
+    /// @end: End of the region (exclusive).
+    pub fn map_io(&mut self, start: u16, end: u16, handler: Box<dyn IoHandler>)
+        -> Result<(), &'static str> {
+        return self.hardware.map_io(start, end, handler);
+    }
+
     /// Increases the memory by the specified additional bytes.
     ///
     /// Returns error if new size would become more than maxed allowed (65536)
@@ -115,6 +417,45 @@ impl Emulator {
         return self.hardware.increase_memory(additional);
     }
 
+    /// Decreases the memory by the specified bytes, from the end of the memory.
+    ///
+    /// Returns error if memory would become empty, or if it's smaller than
+    /// the requested bytes. Memory won't be touched if error return.
+    ///
+    /// @remove: Bytes to remove from the memory.
+    pub fn decrease_memory(&mut self, remove: u16) -> Result<u16, &'static str> {
+        return self.hardware.decrease_memory(remove);
+    }
+
+    /// Returns size of the memory.
+    pub fn memory_size(&self) -> u16 {
+        return self.hardware.get_memory_size();
+    }
+
+    /// Returns end of the highest data that is loaded into the memory.
+    /// i.e. memory from this address to the end is never loaded.
+    pub fn loaded_size(&self) -> u16 {
+        return self.hardware.get_loaded_extent();
+    }
+
+    /// Renders "count" words of the memory starting from "start", one word per
+    /// line: address, the binary word, and its disassembly. Words of memory-mapped
+    /// I/O regions are shown as they are in the memory, handlers are not called.
+    /// Range is clamped to the end of the memory.
+    pub fn dump(&self, start: u16, count: u16) -> String {
+        let memory = self.hardware.get_memory();
+
+        let start = (start as usize).min(memory.len());
+        let end = (start + count as usize).min(memory.len());
+
+        let lines: Vec<String> = (start..end)
+            .map(|address| format!("0x{:04x}  0b{:016b}  {}",
+                                   address, memory[address], disasm::disassemble(memory[address])))
+            .collect();
+
+        return lines.join("\n");
+    }
+
 }
 
 #[cfg(test)]
@@ -123,6 +464,299 @@ mod tests {
     use super::*;
     use std::io::Write;
     use std::env::temp_dir;
+    use std::fs;
+
+    // Golden bots and their recorded traces. See tests/golden/README.md
+    const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+    // Name of each golden bot, and its memory size.
+    const GOLDEN_BOTS: [(&str, u16); 1] = [("counter", 17)];
+
+    #[test]
+    fn self_test() {
+        let mut emulator = Emulator::new(8);
+        emulator.self_test().unwrap();
+
+        // It can be run again on the same emulator.
+        emulator.self_test().unwrap();
+        emulator.hardware.compare_memory(&vec![0; 8]);
+    }
+
+    #[test]
+    fn run_to_completion_halted() {
+        let mut emulator = Emulator::new(3);
+        emulator.set_end_of_memory_behavior(EndOfMemoryBehavior::Halt);
+
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b0110_010_000000111u16, // Set R2 to 7
+                        0b0010_000001_000010u16]; // Add R1 to R2
+        emulator.load(&code, 0).unwrap();
+
+        let summary = emulator.run_to_completion(100);
+        assert_eq!(summary.get_cycles(), 3);
+        assert_eq!(*summary.get_termination_reason(), TerminationReason::Halted);
+        assert_eq!(summary.get_register(2), 12);
+        assert_eq!(summary.get_registers(), [0, 5, 12, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn run_to_completion_error() {
+        let mut emulator = Emulator::new(3);
+
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b1111_111111_111111u16]; // Bad instruction
+        emulator.load(&code, 0).unwrap();
+
+        let summary = emulator.run_to_completion(100);
+        assert_eq!(summary.get_cycles(), 1);
+        assert_eq!(*summary.get_termination_reason(),
+                   TerminationReason::Error(ClockError::UnknownInstruction(0b1111111111111111)));
+        assert_eq!(summary.get_register(1), 5);
+    }
+
+    #[test]
+    fn run_to_completion_budget_exhausted() {
+        let mut emulator = Emulator::new(1);
+
+        // Jumps to itself forever.
+        emulator.load(&vec![0b0000_000001_000000u16], 0).unwrap();
+
+        let summary = emulator.run_to_completion(10);
+        assert_eq!(summary.get_cycles(), 10);
+        assert_eq!(*summary.get_termination_reason(), TerminationReason::BudgetExhausted);
+        assert_eq!(summary.get_registers(), [0; 8]);
+    }
+
+    #[test]
+    fn clock_count() {
+        let mut emulator = Emulator::new(6);
+
+        let code = vec![0u16, 0u16, 0u16, 0u16, 0u16,
+                        0b1111_111111_111111u16]; // Bad instruction
+        emulator.load(&code, 0).unwrap();
+
+        assert_eq!(emulator.clock_count(), 0);
+
+        for _ in 0..5 {
+            emulator.clock().unwrap();
+        }
+        assert_eq!(emulator.clock_count(), 5);
+
+        // Failed clock isn't counted.
+        assert_eq!(emulator.clock().is_err(), true);
+        assert_eq!(emulator.clock_count(), 5);
+
+        emulator.reset();
+        assert_eq!(emulator.clock_count(), 0);
+    }
+
+    /// A sys call handler that does nothing.
+    struct NullSyscall {
+    }
+
+    impl SysCallback for NullSyscall {
+        fn syscall(&mut self, _cpu_state: &mut CPUState) {
+        }
+    }
+
+    #[test]
+    fn last_instruction_cost() {
+        let mut emulator = Emulator::new(4);
+        emulator.set_end_of_memory_behavior(EndOfMemoryBehavior::Halt);
+        emulator.register_sys_callback_boxed(Box::new(NullSyscall {}));
+
+        let code = vec![0u16,
+                        0b0000000000_000001u16, // SYSCALL
+                        0b1011_011_000000000u16, 40000u16]; // LOAD_IMMEDIATE R3 40000
+        emulator.load(&code, 0).unwrap();
+
+        assert_eq!(emulator.last_instruction_cost(), 0);
+
+        emulator.clock().unwrap();
+        assert_eq!(emulator.last_instruction_cost(), 1);
+        emulator.clock().unwrap();
+        assert_eq!(emulator.last_instruction_cost(), 5);
+        emulator.clock().unwrap();
+        assert_eq!(emulator.last_instruction_cost(), 2);
+
+        // Halted
+        emulator.clock().unwrap();
+        assert_eq!(emulator.is_halted(), true);
+        assert_eq!(emulator.last_instruction_cost(), 1);
+    }
+
+    #[test]
+    fn reset() {
+        let mut emulator = Emulator::new(4);
+
+        let code = vec![0b0110_001_000000101u16, // Set R1 to 5
+                        0b0110_010_000000111u16, // Set R2 to 7
+                        0b0000_000011_000001u16, // Subroutine to R1 (beyond the memory)
+                        0b1111_111111_111111u16]; // Bad instruction
+        emulator.load(&code, 0).unwrap();
+
+        emulator.clock().unwrap();
+        emulator.clock().unwrap();
+        emulator.clock().unwrap();
+        assert_eq!(emulator.clock().is_err(), true);
+
+        emulator.reset();
+
+        assert_eq!(emulator.hardware.get_registers(), [0; 8]);
+        assert_eq!(emulator.hardware.get_program_counter(), 0);
+        emulator.hardware.compare_memory(&code);
+
+        // It can run the program again.
+        emulator.clock().unwrap();
+        assert_eq!(emulator.hardware.get_registers(), [0, 5, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn get_program_counter() {
+        let mut emulator = Emulator::new(3);
+
+        emulator.load(&vec![0u16, 0b1111_111111_111111u16], 0).unwrap();
+        assert_eq!(emulator.get_program_counter(), 0);
+
+        emulator.clock().unwrap();
+        assert_eq!(emulator.get_program_counter(), 1);
+
+        // Bad instruction
+        assert_eq!(emulator.clock().is_err(), true);
+        assert_eq!(emulator.get_program_counter(), 1);
+    }
+
+    #[test]
+    fn get_registers() {
+        let mut emulator = Emulator::new(1);
+
+        emulator.load(&vec![0b0110_010_001001101u16], 0).unwrap(); // Set R2 to 77
+        emulator.clock().unwrap();
+
+        assert_eq!(emulator.get_register(2), Ok(77));
+        assert_eq!(emulator.get_register(7), Ok(0));
+        assert_eq!(emulator.get_register(8).is_err(), true);
+        assert_eq!(emulator.get_registers(), [0, 0, 77, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn read_write_memory() {
+        let mut emulator = Emulator::new(4);
+        emulator.load(&vec![12, 13], 1).unwrap();
+
+        assert_eq!(emulator.read_memory(0), Ok(0));
+        assert_eq!(emulator.read_memory(2), Ok(13));
+
+        emulator.write_memory(3, 1000).unwrap();
+        assert_eq!(emulator.read_memory(3), Ok(1000));
+        emulator.hardware.compare_memory(&vec![0, 12, 13, 1000]);
+
+        assert_eq!(emulator.read_memory(4).is_err(), true);
+        assert_eq!(emulator.write_memory(4, 1).is_err(), true);
+        assert_eq!(emulator.write_memory(65535, 1).is_err(), true);
+    }
+
+    #[test]
+    fn load_segments() {
+        let mut emulator = Emulator::new(6);
+
+        emulator.load_segments(&[(4, vec![40, 41]), (0, vec![10, 11])]).unwrap();
+        emulator.hardware.compare_memory(&vec![10, 11, 0, 0, 40, 41]);
+        assert_eq!(emulator.loaded_size(), 6);
+    }
+
+    #[test]
+    fn load_segments_overlapping() {
+        let mut emulator = Emulator::new(6);
+
+        assert_eq!(emulator.load_segments(&[(0, vec![10, 11, 12]), (2, vec![20])]),
+                   Err("Segments are overlapping."));
+        emulator.hardware.compare_memory(&vec![0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn load_segments_out_of_memory() {
+        let mut emulator = Emulator::new(6);
+
+        assert_eq!(emulator.load_segments(&[(0, vec![10, 11]), (5, vec![50, 51])]),
+                   Err("Out of memory: Segment won't fit in memory starting from specified address."));
+        emulator.hardware.compare_memory(&vec![0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn load_from_file_endian() {
+        let mut big_endian_file = temp_dir();
+        big_endian_file.push("test_binary_code_big_endian_w8q1zt");
+        let mut f = File::create(&big_endian_file).unwrap();
+        f.write(&[0b00010000u8, 0b01000010u8]).unwrap();
+        f.flush().unwrap();
+
+        let mut little_endian_file = temp_dir();
+        little_endian_file.push("test_binary_code_little_endian_w8q1zt");
+        let mut f = File::create(&little_endian_file).unwrap();
+        f.write(&[0b01000010u8, 0b00010000u8]).unwrap();
+        f.flush().unwrap();
+
+        let mut emulator = Emulator::new(2);
+        emulator.load_from_file_endian(&String::from(big_endian_file.to_str().unwrap()), 0, false).unwrap();
+        emulator.load_from_file_endian(&String::from(little_endian_file.to_str().unwrap()), 1, true).unwrap();
+
+        emulator.hardware.compare_memory(&vec![0b0001000001000010u16, 0b0001000001000010u16]);
+
+        fs::remove_file(big_endian_file).unwrap();
+        fs::remove_file(little_endian_file).unwrap();
+    }
+
+    #[test]
+    fn load_from_bytes() {
+        let code = vec![0b00010000u8, 0b01000010u8,
+                        0b00100000u8, 0b10110011u8,
+                        0b00001011u8, 0b10111000u8];
+
+        let mut code_file = temp_dir();
+        code_file.push("test_binary_code_2_k39dmx0");
+        let mut f = File::create(&code_file).unwrap();
+        f.write(&code).unwrap();
+        f.flush().unwrap();
+
+        let mut file_emulator = Emulator::new(5);
+        file_emulator.load_from_file(&String::from(code_file.to_str().unwrap()), 1).unwrap();
+
+        let mut bytes_emulator = Emulator::new(5);
+        bytes_emulator.load_from_bytes(&code, 1).unwrap();
+
+        let expected_memory = vec![0u16, 0b0001000001000010u16, 0b0010000010110011u16,
+                                   0b0000101110111000u16, 0u16];
+        file_emulator.hardware.compare_memory(&expected_memory);
+        bytes_emulator.hardware.compare_memory(&expected_memory);
+
+        // Odd number of bytes.
+        assert_eq!(bytes_emulator.load_from_bytes(&code[..3], 0),
+                   Err("File should be multiply of two-bytes."));
+    }
+
+    #[test]
+    fn run() {
+        let mut emulator = Emulator::new(5);
+        emulator.set_end_of_memory_behavior(EndOfMemoryBehavior::Halt);
+
+        // Memory is all NOPs.
+        assert_eq!(emulator.run(10), Ok(5));
+        assert_eq!(emulator.is_halted(), true);
+
+        // Halted CPU executes nothing.
+        assert_eq!(emulator.run(10), Ok(0));
+    }
+
+    #[test]
+    fn run_stops_at_max_clocks() {
+        let mut emulator = Emulator::new(5);
+
+        assert_eq!(emulator.run(3), Ok(3));
+        assert_eq!(emulator.run(2), Ok(2));
+
+        // Goes beyond the memory.
+        assert_eq!(emulator.run(1), Err(ClockError::PcOutOfBounds(5)));
+    }
 
     #[test]
     fn load_from_file() {
@@ -147,4 +781,218 @@ mod tests {
                                    0b0000000000000000u16, 0b0000101110111000u16];
         emulator.hardware.compare_memory(&expected_memory);
     }
+
+    #[test]
+    fn fill_memory() {
+        let mut emulator = Emulator::new(4);
+
+        emulator.fill_memory(0, 4, 0xFFFF).unwrap();
+        assert_eq!(emulator.read_memory(0), Ok(0xFFFF));
+        assert_eq!(emulator.read_memory(3), Ok(0xFFFF));
+
+        assert_eq!(emulator.fill_memory(2, 3, 0), Err("Fill range is out of the memory."));
+        assert_eq!(emulator.read_memory(2), Ok(0xFFFF));
+    }
+
+    #[test]
+    fn dump() {
+        let mut emulator = Emulator::new(4);
+        emulator.load(&vec![0b0110_001_001111000u16, // SET R1 120
+                            0b0010_000001_000010u16], // ADD R1 R2
+                      1).unwrap();
+
+        assert_eq!(emulator.dump(1, 2),
+                   "0x0001  0b0110001001111000  SET R1 120\n\
+                    0x0002  0b0010000001000010  ADD R1 R2");
+
+        // Clamped to the end of the memory.
+        assert_eq!(emulator.dump(3, 10), "0x0003  0b0000000000000000  NOP");
+        assert_eq!(emulator.dump(10, 10), "");
+    }
+
+    #[test]
+    fn new_with_limits() {
+        let mut emulator = Emulator::new_with_limits(1, 2);
+
+        // Calls itself until the call stack is full.
+        emulator.load(&vec![0b0000_000011_000000u16], 0).unwrap();
+
+        emulator.clock().unwrap();
+        emulator.clock().unwrap();
+        assert_eq!(emulator.clock(), Err(ClockError::CallStackOverflow));
+    }
+
+    /// Records every traced instruction.
+    struct RecordingTrace {
+        records: Vec<(u16, u16)>,
+    }
+
+    impl InstructionTrace for RecordingTrace {
+        fn trace(&mut self, program_counter: u16, instruction: u16) {
+            self.records.push((program_counter, instruction));
+        }
+    }
+
+    #[test]
+    fn register_trace() {
+        let mut emulator = Emulator::new(5);
+        let code = vec![0b0110_001_000000100u16, // SET R1 4
+                        0b0000_000001_000001u16, // JUMP R1 (4)
+                        0u16,
+                        0u16,
+                        0b0000000000_000011u16]; // HALT
+        emulator.load(&code, 0).unwrap();
+
+        let trace = std::rc::Rc::new(Mutex::new(RecordingTrace { records: Vec::new() }));
+        let weak_trace = std::rc::Rc::downgrade(&trace);
+        emulator.register_trace(weak_trace);
+
+        assert_eq!(emulator.run(10).is_ok(), true);
+
+        assert_eq!(trace.lock().unwrap().records,
+                   vec![(0, 0b0110_001_000000100u16),
+                        (1, 0b0000_000001_000001u16),
+                        (4, 0b0000000000_000011u16)]);
+    }
+
+    #[test]
+    fn clear_error() {
+        let mut emulator = Emulator::new(3);
+        emulator.load(&vec![0b1111_111111_111111u16, // Bad instruction
+                            0b0110_001_001111000u16], // SET R1 120
+                      0).unwrap();
+        emulator.write_memory(2, 17).unwrap();
+
+        assert_eq!(emulator.clock().is_err(), true);
+        assert_eq!(emulator.clock(), Err(ClockError::HardwareInErrorState));
+
+        assert_eq!(emulator.clear_error(Some(3)), Err("Program counter is out of memory."));
+        assert_eq!(emulator.clock(), Err(ClockError::HardwareInErrorState));
+
+        emulator.clear_error(Some(1)).unwrap();
+        emulator.clock().unwrap();
+        assert_eq!(emulator.get_register(1), Ok(120));
+        assert_eq!(emulator.get_program_counter(), 2);
+        // Memory is untouched.
+        assert_eq!(emulator.read_memory(2), Ok(17));
+
+        // Without a new program counter, the failed instruction runs again.
+        emulator.set_program_counter(0).unwrap();
+        assert_eq!(emulator.clock().is_err(), true);
+        emulator.clear_error(None).unwrap();
+        assert_eq!(emulator.get_program_counter(), 0);
+        assert_eq!(emulator.clock(), Err(ClockError::UnknownInstruction(0b1111111111111111)));
+    }
+
+    #[test]
+    fn set_program_counter() {
+        let mut emulator = Emulator::new(4);
+        emulator.load(&vec![0b0110_001_001111000u16], 2).unwrap(); // SET R1 120
+
+        emulator.set_program_counter(2).unwrap();
+        emulator.clock().unwrap();
+        assert_eq!(emulator.get_register(1), Ok(120));
+        assert_eq!(emulator.get_program_counter(), 3);
+
+        assert_eq!(emulator.set_program_counter(4), Err("Program counter is out of memory."));
+        assert_eq!(emulator.get_program_counter(), 3);
+    }
+
+    #[test]
+    fn load_zero_size_memory() {
+        let mut emulator = Emulator::new(0);
+
+        assert_eq!(emulator.load(&vec![0b0110_001_001111000u16], 0),
+                   Err("Cannot load into zero-size memory."));
+        assert_eq!(emulator.load_program(&vec![0b0110_001_001111000u16], 0),
+                   Err("Cannot load into zero-size memory."));
+    }
+
+    #[test]
+    fn load_program() {
+        let mut emulator = Emulator::new(4);
+        emulator.load_program(&vec![0b0110_001_001111000u16], 2).unwrap(); // SET R1 120
+        assert_eq!(emulator.get_program_counter(), 2);
+
+        emulator.clock().unwrap();
+        assert_eq!(emulator.get_register(1), Ok(120));
+
+        // Data is out of memory, so program counter is untouched.
+        assert_eq!(emulator.load_program(&vec![1, 2], 3).is_err(), true);
+        assert_eq!(emulator.get_program_counter(), 3);
+    }
+
+    #[test]
+    fn load_program_from_file() {
+        let mut code_file = temp_dir();
+        code_file.push("test_binary_code_2_k28xhdp");
+
+        let mut f = File::create(&code_file).unwrap();
+        f.write(&[0b01100010u8, 0b01111000u8]).unwrap(); // SET R1 120
+        f.flush().unwrap();
+
+        let mut emulator = Emulator::new(4);
+        emulator.load_program_from_file(&String::from(code_file.to_str().unwrap()), 2).unwrap();
+        assert_eq!(emulator.get_program_counter(), 2);
+
+        emulator.clock().unwrap();
+        assert_eq!(emulator.get_register(1), Ok(120));
+        assert_eq!(emulator.get_program_counter(), 3);
+    }
+
+    /// Runs a golden bot tick by tick until it halts, and returns state of the
+    /// CPU after each tick: program counter followed by the registers.
+    fn run_golden_bot(name: &str, memory_size: u16) -> Vec<String> {
+        let mut emulator = Emulator::new(memory_size);
+        emulator.set_end_of_memory_behavior(EndOfMemoryBehavior::Halt);
+        emulator.load_from_file(&format!("{}/{}.asm.bin", GOLDEN_DIR, name), 0).unwrap();
+
+        let mut states = Vec::new();
+        while !emulator.is_halted() {
+            assert!(states.len() < 10000, "Golden bot [{}] did not halt.", name);
+
+            emulator.clock().unwrap();
+
+            let registers: Vec<String> = emulator.hardware.get_registers().iter()
+                .map(|register| register.to_string())
+                .collect();
+            states.push(format!("{} {}",
+                                emulator.hardware.get_program_counter(),
+                                registers.join(" ")));
+        }
+
+        return states;
+    }
+
+    #[test]
+    fn replay_golden_bots() {
+        for (name, memory_size) in GOLDEN_BOTS.iter() {
+            let trace = fs::read_to_string(format!("{}/{}.trace", GOLDEN_DIR, name)).unwrap();
+            let expected_states: Vec<&str> = trace.lines()
+                .filter(|line| !line.starts_with("#"))
+                .collect();
+
+            let states = run_golden_bot(name, *memory_size);
+
+            for (tick, (expected, actual)) in expected_states.iter().zip(states.iter()).enumerate() {
+                assert_eq!(expected, actual,
+                           "Golden bot [{}] diverged at tick {}", name, tick + 1);
+            }
+            assert_eq!(expected_states.len(), states.len(),
+                       "Golden bot [{}] halted after a different number of ticks", name);
+        }
+    }
+
+    /// Re-records the traces of the golden bots. Run it with:
+    /// cargo test record_golden_traces -- --ignored
+    #[test]
+    #[ignore]
+    fn record_golden_traces() {
+        for (name, memory_size) in GOLDEN_BOTS.iter() {
+            let states = run_golden_bot(name, *memory_size);
+            let trace = format!("# program_counter r0 r1 r2 r3 r4 r5 r6 r7\n{}\n",
+                                states.join("\n"));
+            fs::write(format!("{}/{}.trace", GOLDEN_DIR, name), trace).unwrap();
+        }
+    }
 }
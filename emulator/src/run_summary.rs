@@ -0,0 +1,67 @@
+// This file is part of Sillyverse.
+// Copyright (C) 2017-2020, Aidin Gharibnavaz <aidin@aidinhut.com>
+//
+// Sillyverse is free software: you can redistribute it and/or
+// modify it under the terms of the GNU General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Sillyverse is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Sillyverse. If not, see <http://www.gnu.org/licenses/>.
+
+use ClockError;
+
+/// Reason that a run is stopped.
+#[derive(PartialEq, Debug)]
+pub enum TerminationReason {
+    // CPU is halted.
+    Halted,
+    // Clock returned an error. Value is the error.
+    Error(ClockError),
+    // Maximum number of cycles reached.
+    BudgetExhausted,
+}
+
+/// Summary of running a program to its completion.
+/// This struct is used in the public API of the library.
+pub struct RunSummary {
+    cycles: u64,
+    termination_reason: TerminationReason,
+    registers: [u16; 8],
+}
+
+impl RunSummary {
+
+    pub fn new(cycles: u64, termination_reason: TerminationReason, registers: [u16; 8])
+        -> RunSummary {
+
+        RunSummary {
+            cycles: cycles,
+            termination_reason: termination_reason,
+            registers: registers,
+        }
+    }
+
+    /// Number of cycles that executed successfully.
+    pub fn get_cycles(&self) -> u64 {
+        return self.cycles;
+    }
+
+    pub fn get_termination_reason(&self) -> &TerminationReason {
+        return &self.termination_reason;
+    }
+
+    /// Value of the registers when the run stopped.
+    pub fn get_register(&self, index: usize) -> u16 {
+        return self.registers[index];
+    }
+
+    pub fn get_registers(&self) -> [u16; 8] {
+        return self.registers;
+    }
+}
@@ -22,4 +22,15 @@ pub trait SysCallback {
 
     /// Will be called whenever the program requests a sys call.
     fn syscall(&mut self, cpu_state: &mut CPUState);
+}
+
+/// Structure that wants to observe every executed instruction should
+/// implement this trait. Useful for debuggers and coverage tools.
+pub trait InstructionTrace {
+
+    /// Will be called before each instruction is executed.
+    ///
+    /// @program_counter: Address of the instruction.
+    /// @instruction: Raw instruction word.
+    fn trace(&mut self, program_counter: u16, instruction: u16);
 }
\ No newline at end of file
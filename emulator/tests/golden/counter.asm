@@ -0,0 +1,21 @@
+; Golden bot of the replay regression test.
+; Adds 3 to R2 five times. After each addition, a subroutine stores R2
+; in the last word of the memory and counts its set bits into R0.
+; Memory size should be 17, and the end of memory behavior should be Halt.
+SET     R1 5       ; Loop counter
+SET     R3 16      ; Address of the result
+SET     R4 7       ; Start of the loop
+SET     R5 12      ; Address of the subroutine
+SET     R6 3
+SET     R7 17      ; End of the memory
+NOP
+ADD     R6 R2      ; Loop
+SUBROUTINE R5
+DEC_SKIP_IF_ZERO R1
+JUMP    R4
+JUMP    R7
+COPY    R2 M3      ; Subroutine
+COPY    R2 R0
+POPCOUNT R0
+RETURN
+DATA    0